@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+use crate::commit::ConventionalCommit;
+
+/// result of backporting commits to one target branch
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// every commit was picked; the number of commits
+    Picked(usize),
+    /// *commit* conflicted in *files*; the target was left as it was
+    Conflict { commit: String, files: Vec<String> },
+    /// the target could not be backported to, e.g. it has local changes
+    Failed(String),
+}
+
+impl Outcome {
+    /// a failure with the first line git printed for *err*
+    pub fn failed(err: &Error) -> Outcome {
+        let reason = match err {
+            Error::Exec { stderr, .. } if !stderr.trim().is_empty() => stderr.trim().lines().next().unwrap_or_default().to_string(),
+            err => err.to_string(),
+        };
+        Outcome::Failed(reason)
+    }
+
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Outcome::Picked(_))
+    }
+}
+
+/// *message* of a backported commit, with the subject marked as a backport
+/// of *hash*. the conventional type and scope stay in front so the commit
+/// still counts for changelogs and version bumps.
+pub fn backport_message(message: &str, hash: &str) -> String {
+    let (subject, rest) = match message.split_once('\n') {
+        Some((subject, rest)) => (subject, Some(rest)),
+        None => (message, None),
+    };
+    let short = &hash[..hash.len().min(7)];
+    let subject = match ConventionalCommit::from_string(subject) {
+        Ok(mut commit) => {
+            commit.description = format!("{} (backport of {short})", commit.description);
+            commit.subject()
+        }
+        Err(_) => format!("{subject} (backport of {short})"),
+    };
+    match rest {
+        Some(rest) => format!("{subject}\n{rest}"),
+        None => subject,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backport_message, Outcome};
+    use crate::prelude::*;
+
+    #[test]
+    fn message() {
+        let message = "fix(cli)!: foo\n\nbody\n\n(cherry picked from commit 1234567890)";
+        assert_eq!(
+            backport_message(message, "1234567890"),
+            "fix(cli)!: foo (backport of 1234567)\n\nbody\n\n(cherry picked from commit 1234567890)"
+        );
+        assert_eq!(backport_message("update readme", "abcdef12"), "update readme (backport of abcdef1)");
+    }
+
+    #[test]
+    fn failed() {
+        let err = Error::Exec {
+            cmd: "git cherry-pick -x 1234".to_string(),
+            code: Some(1),
+            stderr: "error: bad revision\nfatal: cherry-pick failed\n".to_string(),
+        };
+        assert_eq!(Outcome::failed(&err), Outcome::Failed("error: bad revision".to_string()));
+        assert!(Outcome::Picked(2).is_ok());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::prelude::*;
+
+use crate::exec::GitBackend;
+use crate::util::time;
+
+pub const BACKUP_PREFIX: &str = "refs/gtea/backup/";
+
+/// a saved branch position, stored as `refs/gtea/backup/<branch>/<time>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub branch: String,
+    /// seconds since the unix epoch
+    pub time: u64,
+    pub hash: String,
+}
+
+impl Backup {
+    pub fn gref(&self) -> String {
+        format!("{BACKUP_PREFIX}{}/{}", self.branch, self.time)
+    }
+    /// parse a backup from its ref
+    pub fn from_ref(hash: &str, gref: &str) -> Option<Self> {
+        let (branch, time) = gref.strip_prefix(BACKUP_PREFIX)?.rsplit_once('/')?;
+        Some(Self {
+            branch: branch.to_string(),
+            time: time.parse().ok()?,
+            hash: hash.to_string(),
+        })
+    }
+}
+
+/// save *rev* as a backup of *branch*
+///
+/// backups are never overwritten; a backup taken in the same second as an
+/// existing one is moved to the next free second.
+pub fn save(git: &dyn GitBackend, branch: &str, rev: &str, pwd: &str) -> Result<Backup> {
+    let hash = git.rev_parse(rev, pwd)?;
+    let existing = list(git, Some(branch), pwd)?;
+    let mut time = time::now()?;
+    while existing.iter().any(|v| v.time == time) {
+        time += 1;
+    }
+    let backup = Backup {
+        branch: branch.to_string(),
+        time,
+        hash,
+    };
+    git.update_ref(&backup.gref(), &backup.hash, pwd)?;
+    debug!("saved {} to {}", branch, backup.gref());
+    Ok(backup)
+}
+
+/// backups of *branch*, or of every branch, sorted by branch and time
+pub fn list(git: &dyn GitBackend, branch: Option<&str>, pwd: &str) -> Result<Vec<Backup>> {
+    let mut backups: Vec<Backup> = git
+        .refs(BACKUP_PREFIX, pwd)?
+        .iter()
+        .filter_map(|(hash, gref)| Backup::from_ref(hash, gref))
+        .filter(|v| branch.map(|b| v.branch == b).unwrap_or(true))
+        .collect();
+    backups.sort_by(|a, b| (&a.branch, a.time).cmp(&(&b.branch, b.time)));
+    Ok(backups)
+}
+
+pub fn delete(git: &dyn GitBackend, backup: &Backup, pwd: &str) -> Result<()> {
+    git.delete_ref(&backup.gref(), pwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Backup;
+    use crate::exec::MockBackend;
+
+    #[test]
+    fn from_ref() {
+        let backup = Backup::from_ref("1111", "refs/gtea/backup/feat/api/1792313593").unwrap();
+        assert_eq!(backup.branch, "feat/api");
+        assert_eq!(backup.time, 1792313593);
+        assert_eq!(backup.gref(), "refs/gtea/backup/feat/api/1792313593");
+        assert_eq!(Backup::from_ref("1111", "refs/gtea/backup/feat/api"), None);
+        assert_eq!(Backup::from_ref("1111", "refs/heads/feat/api/1"), None);
+    }
+
+    #[test]
+    fn list() {
+        let git = MockBackend::new();
+        git.script(
+            "refs",
+            "2222 refs/gtea/backup/main/20\n1111 refs/gtea/backup/main/10\n3333 refs/gtea/backup/feat/main/5",
+        );
+        let backups = super::list(&git, Some("main"), "/repo").unwrap();
+        let hashes: Vec<&str> = backups.iter().map(|v| v.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["1111", "2222"]);
+    }
+}
@@ -0,0 +1,224 @@
+use regex::Regex;
+
+use crate::prelude::*;
+
+pub const FILE_NAME: &str = "CHANGELOG.md";
+pub const UNRELEASED: &str = "Unreleased";
+
+const HEADER: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).";
+
+/// a `## [version] - date` section
+#[derive(Debug, PartialEq, Clone)]
+pub struct Section {
+    /// version without `v` prefix, or `Unreleased`
+    pub version: String,
+    pub date: Option<String>,
+    pub body: String,
+}
+
+impl Section {
+    pub fn new(version: &str, date: Option<&str>, body: &str) -> Section {
+        Section {
+            version: version.to_string(),
+            date: date.map(|v| v.to_string()),
+            body: body.trim().to_string(),
+        }
+    }
+
+    fn is_unreleased(&self) -> bool {
+        self.version.eq_ignore_ascii_case(UNRELEASED)
+    }
+
+    fn heading(&self) -> String {
+        match &self.date {
+            Some(date) => format!("## [{}] - {date}", self.version),
+            None => format!("## [{}]", self.version),
+        }
+    }
+}
+
+/// a changelog file in the Keep a Changelog format
+#[derive(Debug, PartialEq)]
+pub struct ChangelogFile {
+    /// everything before the first section
+    pub header: String,
+    pub sections: Vec<Section>,
+    /// `[name]: url` link references at the end of the file
+    pub links: Vec<(String, String)>,
+}
+
+impl Default for ChangelogFile {
+    fn default() -> Self {
+        Self {
+            header: HEADER.to_string(),
+            sections: vec![],
+            links: vec![],
+        }
+    }
+}
+
+impl ChangelogFile {
+    pub fn parse(content: &str) -> Result<ChangelogFile> {
+        let heading_re = Regex::new(r"^## \[?([^\]\s]+)\]?(?:\s+-\s+(\S+))?")?;
+        let link_re = Regex::new(r"^\[([^\]]+)\]:\s*(\S+)\s*$")?;
+
+        let mut lines: Vec<&str> = content.lines().collect();
+        // link references are the trailing block of `[name]: url` lines
+        let mut links = vec![];
+        while let Some(line) = lines.last() {
+            if line.trim().is_empty() {
+                lines.pop();
+                continue;
+            }
+            match link_re.captures(line) {
+                Some(captures) => {
+                    links.push((captures[1].to_string(), captures[2].to_string()));
+                    lines.pop();
+                }
+                None => break,
+            }
+        }
+        links.reverse();
+
+        let mut header = vec![];
+        let mut sections: Vec<(Section, Vec<&str>)> = vec![];
+        for line in lines {
+            match heading_re.captures(line) {
+                Some(captures) => {
+                    let version = captures[1].trim_start_matches('v');
+                    let date = captures.get(2).map(|v| v.as_str());
+                    sections.push((Section::new(version, date, ""), vec![]));
+                }
+                None => match sections.last_mut() {
+                    Some((_, body)) => body.push(line),
+                    None => header.push(line),
+                },
+            }
+        }
+        let sections = sections
+            .into_iter()
+            .map(|(mut section, body)| {
+                section.body = body.join("\n").trim().to_string();
+                section
+            })
+            .collect();
+        Ok(ChangelogFile {
+            header: header.join("\n").trim().to_string(),
+            sections,
+            links,
+        })
+    }
+
+    /// add *section*, replacing the section of the same version.
+    ///
+    /// a released section also replaces the `Unreleased` section, since its
+    /// commits now belong to that version.
+    pub fn insert(&mut self, section: Section) {
+        let same = self.sections.iter().position(|v| v.version.eq_ignore_ascii_case(&section.version));
+        let unreleased = self.sections.iter().position(|v| v.is_unreleased());
+        match (same, unreleased) {
+            (Some(i), _) => {
+                self.sections[i] = section;
+                // drop a leftover unreleased section of the same commits
+                if let Some(j) = unreleased.filter(|j| *j != i) {
+                    self.sections.remove(j);
+                }
+            }
+            (None, Some(i)) if !section.is_unreleased() => self.sections[i] = section,
+            _ => self.sections.insert(0, section),
+        }
+    }
+
+    /// set the link reference *name*, keeping the position of an existing one
+    pub fn set_link(&mut self, name: &str, url: &str) {
+        match self.links.iter_mut().find(|(v, _)| v.eq_ignore_ascii_case(name)) {
+            Some(link) => link.1 = url.to_string(),
+            None => {
+                // new versions go right after the unreleased link
+                let index = match self.links.first() {
+                    Some((v, _)) if v.eq_ignore_ascii_case(UNRELEASED) => 1,
+                    _ => 0,
+                };
+                let index = if name.eq_ignore_ascii_case(UNRELEASED) { 0 } else { index };
+                self.links.insert(index, (name.to_lowercase(), url.to_string()));
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ChangelogFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.header)?;
+        for section in &self.sections {
+            write!(f, "\n{}\n", section.heading())?;
+            if !section.body.is_empty() {
+                write!(f, "\n{}\n", section.body)?;
+            }
+        }
+        if !self.links.is_empty() {
+            writeln!(f)?;
+            for (name, url) in &self.links {
+                writeln!(f, "[{name}]: {url}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangelogFile, Section};
+
+    const CONTENT: &str = "# Changelog
+
+intro
+
+## [Unreleased]
+
+### Features
+
+- foo (1111111)
+
+## [1.0.0] - 2024-01-01
+
+### Bug Fixes
+
+- bar (2222222)
+
+[unreleased]: https://example.com/compare/v1.0.0...HEAD
+[1.0.0]: https://example.com/releases/tag/v1.0.0
+";
+
+    #[test]
+    fn roundtrip() {
+        let file = ChangelogFile::parse(CONTENT).unwrap();
+        assert_eq!(file.header, "# Changelog\n\nintro");
+        assert_eq!(file.sections.len(), 2);
+        assert_eq!(file.sections[1], Section::new("1.0.0", Some("2024-01-01"), "### Bug Fixes\n\n- bar (2222222)"));
+        assert_eq!(file.links.len(), 2);
+        assert_eq!(file.to_string(), CONTENT);
+    }
+
+    #[test]
+    fn insert() {
+        let mut file = ChangelogFile::parse(CONTENT).unwrap();
+        let section = Section::new("1.1.0", Some("2024-02-01"), "### Features\n\n- foo (1111111)");
+        file.insert(section.clone());
+        file.set_link("1.1.0", "https://example.com/compare/v1.0.0...v1.1.0");
+        let versions: Vec<&str> = file.sections.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.1.0", "1.0.0"]);
+        let names: Vec<&str> = file.links.iter().map(|v| v.0.as_str()).collect();
+        assert_eq!(names, vec!["unreleased", "1.1.0", "1.0.0"]);
+
+        // idempotent
+        let content = file.to_string();
+        file.insert(section);
+        assert_eq!(file.to_string(), content);
+        assert_eq!(ChangelogFile::parse(&content).unwrap(), file);
+    }
+}
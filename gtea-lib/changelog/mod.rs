@@ -0,0 +1,234 @@
+use serde::Serialize;
+
+use crate::prelude::*;
+
+use crate::commit::history::{self, LogEntry, LogFilter};
+use crate::commit::ConventionalCommit;
+use crate::config::ConfigCommit;
+use crate::exec::GitBackend;
+use crate::version::{self, Version};
+
+pub mod file;
+pub mod render;
+pub mod template;
+pub use self::render::Format;
+
+/// heading for commits that are not conventional or have an unknown type
+pub const OTHER_HEADING: &str = "Other";
+
+/// a commit in the changelog
+#[derive(Debug, Clone)]
+#[derive(Serialize)]
+pub struct Entry {
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    pub time: u64,
+    /// description of a conventional commit, the raw subject otherwise
+    pub subject: String,
+    pub commit: Option<ConventionalCommit>,
+}
+
+impl Entry {
+    pub fn new(entry: &LogEntry) -> Entry {
+        let commit = ConventionalCommit::from_string(&entry.message).ok();
+        let subject = match &commit {
+            Some(commit) => commit.description.clone(),
+            None => entry.subject().to_string(),
+        };
+        Entry {
+            hash: entry.hash.clone(),
+            author: entry.author.clone(),
+            email: entry.email.clone(),
+            time: entry.time,
+            subject,
+            commit,
+        }
+    }
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+    pub fn scope(&self) -> Option<&str> {
+        self.commit.as_ref().and_then(|v| v.scope.as_deref())
+    }
+    /// description of the breaking change, if the commit is one
+    pub fn breaking(&self) -> Option<&str> {
+        let commit = self.commit.as_ref().filter(|v| v.is_breaking())?;
+        Some(commit.breaking_change().unwrap_or(&commit.description))
+    }
+    /// commit made by `gtea release`
+    pub fn is_release(&self) -> bool {
+        self.commit
+            .as_ref()
+            .is_some_and(|v| v.kind == "chore" && v.scope.as_deref() == Some("release"))
+    }
+}
+
+/// commits of one type
+#[derive(Debug, Clone)]
+#[derive(Serialize)]
+pub struct Group {
+    pub heading: String,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Default)]
+#[derive(Serialize)]
+pub struct Changelog {
+    /// breaking changes, also listed in their group
+    pub breaking: Vec<Entry>,
+    /// groups in the order of `[[commit.types]]`, followed by `Other`
+    pub groups: Vec<Group>,
+}
+
+impl Changelog {
+    /// group *entries* by commit type, using the headings from *config*.
+    /// release commits are left out.
+    pub fn new(entries: &[LogEntry], config: &ConfigCommit) -> Changelog {
+        let mut groups: Vec<Group> = config
+            .types
+            .iter()
+            .map(|v| Group {
+                heading: v.heading().to_string(),
+                entries: vec![],
+            })
+            .chain([Group {
+                heading: OTHER_HEADING.to_string(),
+                entries: vec![],
+            }])
+            .collect();
+        let mut breaking = vec![];
+        for entry in entries.iter().map(Entry::new).filter(|v| !v.is_release()) {
+            let index = entry
+                .commit
+                .as_ref()
+                .and_then(|commit| config.types.iter().position(|v| v.name == commit.kind))
+                .unwrap_or(config.types.len());
+            if entry.breaking().is_some() {
+                breaking.push(entry.clone());
+            }
+            groups[index].entries.push(entry);
+        }
+        groups.retain(|v| !v.entries.is_empty());
+        Changelog { breaking, groups }
+    }
+
+    /// keep only commits of *types*, if any are given, and drop commits of
+    /// *exclude*. commits without a type only pass an empty *types*.
+    pub fn filter_types(&mut self, types: &[String], exclude: &[String]) {
+        let keep = |entry: &Entry| {
+            let kind = entry.commit.as_ref().map(|v| v.kind.as_str());
+            let included = types.is_empty() || kind.is_some_and(|v| types.iter().any(|t| t == v));
+            let excluded = kind.is_some_and(|v| exclude.iter().any(|t| t == v));
+            included && !excluded
+        };
+        self.breaking.retain(keep);
+        for group in &mut self.groups {
+            group.entries.retain(keep);
+        }
+        self.groups.retain(|v| !v.entries.is_empty());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+/// tags that name a release version, like `v1.2.3` or `1.2.3`, highest
+/// version first. pre-releases of a released version are left out, their
+/// changes belong to the release.
+pub fn release_tags(git: &dyn GitBackend, pwd: &str) -> Result<Vec<String>> {
+    let versions = version::get_versions(git, pwd)?;
+    let released: Vec<Version> = versions.iter().filter(|(_, v)| !v.is_prerelease()).map(|(_, v)| v.clone()).collect();
+    Ok(versions
+        .into_iter()
+        .filter(|(_, v)| !v.is_prerelease() || !released.contains(&Version::new(v.major, v.minor, v.patch)))
+        .map(|(tag, _)| tag)
+        .collect())
+}
+
+/// tags of stable releases, without pre-releases, highest first
+pub fn stable_tags(git: &dyn GitBackend, pwd: &str) -> Result<Vec<String>> {
+    let versions = version::get_versions(git, pwd)?;
+    Ok(versions.into_iter().filter(|(_, v)| !v.is_prerelease()).map(|(tag, _)| tag).collect())
+}
+
+/// which commits go into a changelog, besides the range
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub log: LogFilter,
+    pub types: Vec<String>,
+    pub exclude_types: Vec<String>,
+}
+
+/// changelog of the commits in *range* passing *filter*
+pub fn get_changelog(
+    git: &dyn GitBackend,
+    range: &str,
+    filter: &Filter,
+    config: &ConfigCommit,
+    pwd: &str,
+) -> Result<Changelog> {
+    let entries = history::get_log(git, range, &filter.log, pwd)?;
+    let mut changelog = Changelog::new(&entries, config);
+    changelog.filter_types(&filter.types, &filter.exclude_types);
+    Ok(changelog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{release_tags, stable_tags, Changelog};
+    use crate::commit::history::LogEntry;
+    use crate::config::ConfigCommit;
+    use crate::exec::MockBackend;
+
+    fn entry(hash: &str, message: &str) -> LogEntry {
+        LogEntry {
+            hash: hash.to_string(),
+            author: "foo".to_string(),
+            email: "foo@bar".to_string(),
+            time: 0,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn groups() {
+        let entries = vec![
+            entry("1111", "fix(cli): bar"),
+            entry("2222", "feat!: foo"),
+            entry("3333", "update readme"),
+            entry("4444", "feat(lib): baz\n\nBREAKING CHANGE: qux"),
+            entry("5555", "chore(release): v1.2.0"),
+        ];
+        let changelog = Changelog::new(&entries, &ConfigCommit::default());
+        let headings: Vec<&str> = changelog.groups.iter().map(|v| v.heading.as_str()).collect();
+        assert_eq!(headings, vec!["Features", "Bug Fixes", "Other"]);
+        assert_eq!(changelog.groups[0].entries.len(), 2);
+        let breaking: Vec<Option<&str>> = changelog.breaking.iter().map(|v| v.breaking()).collect();
+        assert_eq!(breaking, vec![Some("foo"), Some("qux")]);
+        assert_eq!(changelog.groups[2].entries[0].subject, "update readme");
+    }
+
+    #[test]
+    fn filter_types() {
+        let entries = vec![entry("1111", "fix: bar"), entry("2222", "feat!: foo"), entry("3333", "update readme")];
+        let mut changelog = Changelog::new(&entries, &ConfigCommit::default());
+        changelog.filter_types(&[], &["feat".to_string()]);
+        let headings: Vec<&str> = changelog.groups.iter().map(|v| v.heading.as_str()).collect();
+        assert_eq!(headings, vec!["Bug Fixes", "Other"]);
+        assert!(changelog.breaking.is_empty());
+        changelog.filter_types(&["fix".to_string()], &[]);
+        assert_eq!(changelog.groups.len(), 1);
+    }
+
+    #[test]
+    fn tags() {
+        let git = MockBackend::new();
+        // `git tag --sort=-v:refname` puts pre-releases above their release
+        let tags = "v1.2.0-nightly.1\nv1.1.0-nightly.1\nv1.1.0\nfoo\nv1.0.0";
+        git.script("tags", tags).script("tags", tags);
+        assert_eq!(release_tags(&git, "/repo").unwrap(), vec!["v1.2.0-nightly.1", "v1.1.0", "v1.0.0"]);
+        assert_eq!(stable_tags(&git, "/repo").unwrap(), vec!["v1.1.0", "v1.0.0"]);
+    }
+}
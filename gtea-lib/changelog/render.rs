@@ -0,0 +1,168 @@
+use std::str::FromStr;
+
+use crate::prelude::*;
+use crate::util::colors::{COLOR_BOLD, COLOR_RED, COLOR_RESET, COLOR_YELLOW};
+
+use super::{Changelog, Entry};
+
+pub const BREAKING_HEADING: &str = "BREAKING CHANGES";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Terminal,
+    Markdown,
+    Json,
+    Plain,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "terminal" => Ok(Format::Terminal),
+            "markdown" | "md" => Ok(Format::Markdown),
+            "json" => Ok(Format::Json),
+            "plain" => Ok(Format::Plain),
+            _ => Err(make_err!(Parse, "unknown changelog format `{s}`.")),
+        }
+    }
+}
+
+/// render *changelog* as *format*
+pub fn render(changelog: &Changelog, format: Format) -> Result<String> {
+    match format {
+        Format::Terminal => Ok(terminal(changelog)),
+        Format::Markdown => Ok(markdown(changelog)),
+        Format::Json => Ok(serde_json::to_string_pretty(changelog)?),
+        Format::Plain => Ok(plain(changelog)),
+    }
+}
+
+/// `scope: ` prefix of an entry, wrapped in *bold*
+fn scope(entry: &Entry, bold: (&str, &str)) -> String {
+    match entry.scope() {
+        Some(scope) => format!("{}{scope}:{} ", bold.0, bold.1),
+        None => String::new(),
+    }
+}
+
+fn terminal(changelog: &Changelog) -> String {
+    let mut out = String::new();
+    let bold = (COLOR_BOLD, COLOR_RESET);
+    if !changelog.breaking.is_empty() {
+        out.push_str(&format!("{COLOR_RED}{BREAKING_HEADING}{COLOR_RESET}\n"));
+        for entry in &changelog.breaking {
+            out.push_str(&format!(
+                " - {}{} {COLOR_YELLOW}{}{COLOR_RESET}\n",
+                scope(entry, bold),
+                entry.breaking().unwrap_or_default(),
+                entry.short_hash()
+            ));
+        }
+        out.push('\n');
+    }
+    for group in &changelog.groups {
+        out.push_str(&format!("{COLOR_BOLD}{}{COLOR_RESET}\n", group.heading));
+        for entry in &group.entries {
+            out.push_str(&format!(
+                " - {}{} {COLOR_YELLOW}{}{COLOR_RESET}\n",
+                scope(entry, bold),
+                entry.subject,
+                entry.short_hash()
+            ));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+fn markdown(changelog: &Changelog) -> String {
+    let mut out = String::new();
+    let bold = ("**", "**");
+    if !changelog.breaking.is_empty() {
+        out.push_str(&format!("### {BREAKING_HEADING}\n\n"));
+        for entry in &changelog.breaking {
+            out.push_str(&format!(
+                "- {}{} ({})\n",
+                scope(entry, bold),
+                entry.breaking().unwrap_or_default(),
+                entry.short_hash()
+            ));
+        }
+        out.push('\n');
+    }
+    for group in &changelog.groups {
+        out.push_str(&format!("### {}\n\n", group.heading));
+        for entry in &group.entries {
+            out.push_str(&format!("- {}{} ({})\n", scope(entry, bold), entry.subject, entry.short_hash()));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+fn plain(changelog: &Changelog) -> String {
+    let mut out = String::new();
+    let bold = ("", "");
+    if !changelog.breaking.is_empty() {
+        out.push_str(&format!("{BREAKING_HEADING}\n"));
+        for entry in &changelog.breaking {
+            out.push_str(&format!(
+                "  * {}{} ({})\n",
+                scope(entry, bold),
+                entry.breaking().unwrap_or_default(),
+                entry.short_hash()
+            ));
+        }
+        out.push('\n');
+    }
+    for group in &changelog.groups {
+        out.push_str(&format!("{}\n", group.heading));
+        for entry in &group.entries {
+            out.push_str(&format!("  * {}{} ({})\n", scope(entry, bold), entry.subject, entry.short_hash()));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Format};
+    use crate::changelog::Changelog;
+    use crate::commit::history::LogEntry;
+    use crate::config::ConfigCommit;
+
+    fn changelog() -> Changelog {
+        let entries: Vec<LogEntry> = [("1111111111", "feat(cli)!: add foo"), ("2222222222", "fix: bar")]
+            .iter()
+            .map(|(hash, message)| LogEntry {
+                hash: hash.to_string(),
+                author: "foo".to_string(),
+                email: "foo@bar".to_string(),
+                time: 0,
+                message: message.to_string(),
+            })
+            .collect();
+        Changelog::new(&entries, &ConfigCommit::default())
+    }
+
+    #[test]
+    fn markdown() {
+        let expected = "### BREAKING CHANGES\n\n- **cli:** add foo (1111111)\n\n### Features\n\n- **cli:** add foo (1111111)\n\n### Bug Fixes\n\n- bar (2222222)";
+        assert_eq!(render(&changelog(), Format::Markdown).unwrap(), expected);
+    }
+
+    #[test]
+    fn plain() {
+        let expected = "BREAKING CHANGES\n  * cli: add foo (1111111)\n\nFeatures\n  * cli: add foo (1111111)\n\nBug Fixes\n  * bar (2222222)";
+        assert_eq!(render(&changelog(), Format::Plain).unwrap(), expected);
+    }
+
+    #[test]
+    fn json() {
+        let json = render(&changelog(), Format::Json).unwrap();
+        assert!(json.contains("\"heading\": \"Bug Fixes\""));
+    }
+}
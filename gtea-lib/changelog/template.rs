@@ -0,0 +1,224 @@
+use minijinja::Environment;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+use crate::commit::Footer;
+use crate::util::{filepath, time};
+
+use super::{Changelog, Entry};
+
+/// templates shipped with gtea, selected by name
+pub const BUILTIN: [(&str, &str); 4] = [
+    ("keep-a-changelog", include_str!("templates/keep-a-changelog.md.j2")),
+    ("release-notes", include_str!("templates/release-notes.md.j2")),
+    ("slack", include_str!("templates/slack.j2")),
+    ("debian", include_str!("templates/debian.j2")),
+];
+
+/// a commit as seen by templates
+#[derive(Debug, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    pub short_hash: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    pub subject: String,
+    pub body: Option<String>,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    /// description of the breaking change
+    pub breaking: Option<String>,
+    pub trailers: Vec<Footer>,
+}
+
+impl Commit {
+    fn new(entry: &Entry) -> Commit {
+        let commit = entry.commit.as_ref();
+        Commit {
+            hash: entry.hash.clone(),
+            short_hash: entry.short_hash().to_string(),
+            kind: commit.map(|v| v.kind.clone()),
+            scope: entry.scope().map(|v| v.to_string()),
+            subject: entry.subject.clone(),
+            body: commit.and_then(|v| v.body.clone()),
+            author: entry.author.clone(),
+            email: entry.email.clone(),
+            date: time::format_date(entry.time),
+            breaking: entry.breaking().map(|v| v.to_string()),
+            trailers: commit.map(|v| v.footers.clone()).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Group {
+    pub heading: String,
+    pub commits: Vec<Commit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+}
+
+/// everything a changelog template can use
+#[derive(Debug, Serialize)]
+pub struct Context {
+    pub project: String,
+    pub version: Option<String>,
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub date_rfc2822: String,
+    pub groups: Vec<Group>,
+    pub breaking: Vec<Commit>,
+    /// all commits, newest first
+    pub commits: Vec<Commit>,
+    /// authors, most commits first
+    pub contributors: Vec<Contributor>,
+}
+
+impl Context {
+    /// - *time*: release time, seconds since the unix epoch
+    pub fn new(changelog: &Changelog, project: &str, version: Option<&str>, time: u64) -> Context {
+        let mut entries: Vec<&Entry> = changelog.groups.iter().flat_map(|v| &v.entries).collect();
+        entries.sort_by_key(|v| std::cmp::Reverse(v.time));
+        let mut contributors: Vec<Contributor> = vec![];
+        for entry in &entries {
+            match contributors.iter_mut().find(|v| v.email == entry.email) {
+                Some(contributor) => contributor.commits += 1,
+                None => contributors.push(Contributor {
+                    name: entry.author.clone(),
+                    email: entry.email.clone(),
+                    commits: 1,
+                }),
+            }
+        }
+        contributors.sort_by_key(|v| std::cmp::Reverse(v.commits));
+        Context {
+            project: project.to_string(),
+            version: version.map(|v| v.trim_start_matches('v').to_string()),
+            date: time::format_date(time),
+            date_rfc2822: time::format_rfc2822(time),
+            groups: changelog
+                .groups
+                .iter()
+                .map(|v| Group {
+                    heading: v.heading.clone(),
+                    commits: v.entries.iter().map(Commit::new).collect(),
+                })
+                .collect(),
+            breaking: changelog.breaking.iter().map(Commit::new).collect(),
+            commits: entries.into_iter().map(Commit::new).collect(),
+            contributors,
+        }
+    }
+}
+
+/// source of the built-in template *name*, or of the template file at
+/// *name* relative to *pwd*
+pub fn load(name: &str, pwd: &str) -> Result<String> {
+    if let Some((_, source)) = BUILTIN.iter().find(|(v, _)| *v == name) {
+        return Ok(source.to_string());
+    }
+    let path = filepath::join(pwd, name);
+    if !filepath::exists(&path) {
+        let builtin: Vec<&str> = BUILTIN.iter().map(|v| v.0).collect();
+        let builtin = builtin.join(", ");
+        return Err(make_err!(NotFound, "template {path} not found; built-in templates are {builtin}."));
+    }
+    Ok(std::fs::read_to_string(path)?)
+}
+
+/// render the jinja *template* with *context*
+pub fn render(template: &str, context: &Context) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_template("changelog", template)?;
+    let output = env.get_template("changelog")?.render(context)?;
+    Ok(output.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Context, BUILTIN};
+    use crate::changelog::Changelog;
+    use crate::commit::history::LogEntry;
+    use crate::config::ConfigCommit;
+
+    fn context() -> Context {
+        let entries: Vec<LogEntry> = [
+            ("1111111111", "a", 20, "feat(cli)!: add foo\n\nBREAKING CHANGE: no bar"),
+            ("2222222222", "b", 10, "fix: bar"),
+            ("3333333333", "a", 5, "fix(lib): baz"),
+        ]
+        .iter()
+        .map(|(hash, author, time, message)| LogEntry {
+            hash: hash.to_string(),
+            author: author.to_string(),
+            email: format!("{author}@foo"),
+            time: *time,
+            message: message.to_string(),
+        })
+        .collect();
+        let changelog = Changelog::new(&entries, &ConfigCommit::default());
+        Context::new(&changelog, "gtea", Some("v1.0.0"), 0)
+    }
+
+    fn builtin(name: &str) -> &str {
+        BUILTIN.iter().find(|v| v.0 == name).unwrap().1
+    }
+
+    #[test]
+    fn contributors() {
+        let context = context();
+        assert_eq!(context.contributors[0].name, "a");
+        assert_eq!(context.contributors[0].commits, 2);
+        assert_eq!(context.commits[0].short_hash, "1111111");
+    }
+
+    #[test]
+    fn keep_a_changelog() {
+        let expected = "## [1.0.0] - 1970-01-01
+
+### BREAKING CHANGES
+
+- **cli:** no bar (1111111)
+
+### Features
+
+- **cli:** add foo (1111111)
+
+### Bug Fixes
+
+- bar (2222222)
+- **lib:** baz (3333333)";
+        assert_eq!(render(builtin("keep-a-changelog"), &context()).unwrap(), expected);
+    }
+
+    #[test]
+    fn debian() {
+        let expected = "gtea (1.0.0) unstable; urgency=medium
+
+  * cli: add foo
+  * bar
+  * lib: baz
+
+ -- a <a@foo>  Thu, 01 Jan 1970 00:00:00 +0000";
+        assert_eq!(render(builtin("debian"), &context()).unwrap(), expected);
+    }
+
+    #[test]
+    fn builtins() {
+        let context = context();
+        for (name, source) in BUILTIN {
+            let output = render(source, &context).unwrap();
+            assert!(output.contains("bar"), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt;
+
+use nom::{
+    bytes::complete::{tag, take_till1, take_while1},
+    character::complete::{char, space1},
+    combinator::{opt, rest},
+    sequence::delimited,
+    IResult,
+};
+
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// footer token marking a breaking change
+pub const BREAKING_CHANGE: &str = "BREAKING CHANGE";
+
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize)]
+pub struct Footer {
+    pub token: String,
+    pub value: String,
+}
+
+impl fmt::Display for Footer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `Fixes #12` style footers are normalized to `Fixes: #12`
+        write!(f, "{}: {}", self.token, self.value)
+    }
+}
+
+/// drop `#` comment lines and everything below git's scissors line, as
+/// `git commit --cleanup=strip` would
+pub fn strip_comments(input: &str) -> String {
+    let lines: Vec<&str> = input
+        .lines()
+        .take_while(|v| !v.starts_with("# ------------------------ >8 ------------------------"))
+        .filter(|v| !v.starts_with('#'))
+        .map(|v| v.trim_end())
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// a commit message following <https://www.conventionalcommits.org>
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize)]
+pub struct ConventionalCommit {
+    pub kind: String,
+    pub scope: Option<String>,
+    /// `!` after the type/scope
+    pub breaking: bool,
+    pub description: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+}
+
+impl ConventionalCommit {
+    pub fn new(kind: &str, description: &str) -> ConventionalCommit {
+        ConventionalCommit {
+            kind: kind.to_string(),
+            scope: None,
+            breaking: false,
+            description: description.to_string(),
+            body: None,
+            footers: vec![],
+        }
+    }
+
+    /// parse a full commit message
+    pub fn parse(input: &str) -> IResult<&str, ConventionalCommit> {
+        let (input, (kind, scope, breaking, description)) = Self::parse_header(input)?;
+        let (input, message) = rest(input)?;
+        let (body, footers) = Self::split_message(message.trim_matches('\n'));
+        Ok((
+            input,
+            ConventionalCommit {
+                kind: kind.to_string(),
+                scope: scope.map(|v| v.to_string()),
+                breaking,
+                description: description.trim_end().to_string(),
+                body,
+                footers,
+            },
+        ))
+    }
+
+    pub fn from_string(input: &str) -> Result<ConventionalCommit> {
+        let input = input.trim_start_matches('\n');
+        match Self::parse(input) {
+            Ok((_, commit)) => Ok(commit),
+            Err(_) => {
+                let subject = input.lines().next().unwrap_or_default();
+                Err(make_err!(
+                    Parse,
+                    "`{subject}` is not a conventional commit; expected `type(scope): description`."
+                ))
+            }
+        }
+    }
+
+    /// whether the commit is marked breaking by `!` or a `BREAKING CHANGE` footer
+    pub fn is_breaking(&self) -> bool {
+        self.breaking || self.breaking_change().is_some()
+    }
+
+    /// description of the breaking change, taken from the footer
+    pub fn breaking_change(&self) -> Option<&str> {
+        self.footers
+            .iter()
+            .find(|v| Self::is_breaking_token(&v.token))
+            .map(|v| v.value.as_str())
+    }
+
+    /// first footer with *token*, compared case-insensitively
+    pub fn footer(&self, token: &str) -> Option<&str> {
+        self.footers
+            .iter()
+            .find(|v| v.token.eq_ignore_ascii_case(token))
+            .map(|v| v.value.as_str())
+    }
+
+    /// `type(scope)!: description`
+    pub fn subject(&self) -> String {
+        let scope = match &self.scope {
+            Some(scope) => format!("({scope})"),
+            None => String::new(),
+        };
+        let breaking = if self.breaking { "!" } else { "" };
+        format!("{}{scope}{breaking}: {}", self.kind, self.description)
+    }
+
+    fn is_breaking_token(token: &str) -> bool {
+        token == BREAKING_CHANGE || token == "BREAKING-CHANGE"
+    }
+
+    fn parse_header(input: &str) -> IResult<&str, (&str, Option<&str>, bool, &str)> {
+        let (input, kind) = take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_')(input)?;
+        let (input, scope) = opt(delimited(
+            char('('),
+            take_till1(|c| c == ')' || c == '\n'),
+            char(')'),
+        ))(input)?;
+        let (input, breaking) = opt(char('!'))(input)?;
+        let (input, _) = tag(":")(input)?;
+        let (input, _) = space1(input)?;
+        let (input, description) = take_till1(|c| c == '\n')(input)?;
+        Ok((input, (kind, scope, breaking.is_some(), description)))
+    }
+
+    /// `token: value` or `token #value`
+    fn parse_footer(input: &str) -> IResult<&str, Footer> {
+        let (input, token) = match tag::<_, _, nom::error::Error<&str>>(BREAKING_CHANGE)(input) {
+            Ok(v) => v,
+            Err(_) => take_while1(|c: char| c.is_alphanumeric() || c == '-')(input)?,
+        };
+        let (input, value) = match tag::<_, _, nom::error::Error<&str>>(": ")(input) {
+            Ok((input, _)) => rest(input)?,
+            Err(_) => {
+                let (input, _) = tag(" ")(input)?;
+                let (_, _) = tag("#")(input)?;
+                rest(input)?
+            }
+        };
+        Ok((
+            input,
+            Footer {
+                token: token.to_string(),
+                value: value.to_string(),
+            },
+        ))
+    }
+
+    /// split everything after the header into body and footers.
+    ///
+    /// footers are the trailing paragraph when its first line is a footer.
+    fn split_message(message: &str) -> (Option<String>, Vec<Footer>) {
+        if message.trim().is_empty() {
+            return (None, vec![]);
+        }
+        let (body, last) = match message.rfind("\n\n") {
+            Some(i) => (Some(&message[..i]), &message[i + 2..]),
+            None => (None, message),
+        };
+        let is_footer = |line: &str| Self::parse_footer(line).is_ok();
+        if !last.lines().next().is_some_and(is_footer) {
+            return (Some(message.to_string()), vec![]);
+        }
+        let mut footers: Vec<Footer> = vec![];
+        for line in last.lines() {
+            match Self::parse_footer(line) {
+                Ok((_, footer)) => footers.push(footer),
+                Err(_) => {
+                    // continuation of a multi-line footer value
+                    if let Some(footer) = footers.last_mut() {
+                        footer.value = format!("{}\n{line}", footer.value);
+                    }
+                }
+            }
+        }
+        let body = body.map(|v| v.trim_matches('\n').to_string()).filter(|v| !v.is_empty());
+        (body, footers)
+    }
+}
+
+impl fmt::Display for ConventionalCommit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.subject())?;
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{body}")?;
+        }
+        if !self.footers.is_empty() {
+            let footers: Vec<String> = self.footers.iter().map(|v| v.to_string()).collect();
+            write!(f, "\n\n{}", footers.join("\n"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_comments, ConventionalCommit, Footer};
+
+    fn footer(token: &str, value: &str) -> Footer {
+        Footer {
+            token: token.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn header() {
+        let commit = ConventionalCommit::from_string("feat(worktree)!: add foo").unwrap();
+        let mut expected = ConventionalCommit::new("feat", "add foo");
+        expected.scope = Some("worktree".to_string());
+        expected.breaking = true;
+        assert_eq!(commit, expected);
+        assert!(commit.is_breaking());
+
+        let commit = ConventionalCommit::from_string("fix: bar\n").unwrap();
+        assert_eq!(commit, ConventionalCommit::new("fix", "bar"));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(ConventionalCommit::from_string("add foo").is_err());
+        assert!(ConventionalCommit::from_string("feat:add foo").is_err());
+        assert!(ConventionalCommit::from_string("feat(): add foo").is_err());
+        assert!(ConventionalCommit::from_string("feat: ").is_err());
+    }
+
+    #[test]
+    fn body_and_footers() {
+        let input = "fix(cli): handle spaces\n\nfirst paragraph\n\nsecond\nparagraph\n\nBREAKING CHANGE: args are no longer split\nacross lines\nRefs: #12\nFixes #3\nCo-authored-by: foo <foo@bar>\n";
+        let commit = ConventionalCommit::from_string(input).unwrap();
+        assert_eq!(commit.body.as_deref(), Some("first paragraph\n\nsecond\nparagraph"));
+        assert_eq!(
+            commit.footers,
+            vec![
+                footer("BREAKING CHANGE", "args are no longer split\nacross lines"),
+                footer("Refs", "#12"),
+                footer("Fixes", "#3"),
+                footer("Co-authored-by", "foo <foo@bar>"),
+            ]
+        );
+        assert!(commit.is_breaking());
+        assert_eq!(commit.footer("refs"), Some("#12"));
+    }
+
+    #[test]
+    fn body_only() {
+        let input = "docs: readme\n\nnot a footer: just text\n";
+        let commit = ConventionalCommit::from_string(input).unwrap();
+        assert_eq!(commit.body.as_deref(), Some("not a footer: just text"));
+        assert!(commit.footers.is_empty());
+    }
+
+    #[test]
+    fn comments() {
+        let input = "fix: foo\n# a comment\n\nbody  \n# ------------------------ >8 ------------------------\ndiff\n";
+        assert_eq!(strip_comments(input), "fix: foo\n\nbody");
+    }
+
+    #[test]
+    fn format() {
+        let input = "feat(cli)!: add foo\n\nbody\n\nBREAKING CHANGE: bar\nRefs #1";
+        let commit = ConventionalCommit::from_string(input).unwrap();
+        assert_eq!(commit.to_string(), "feat(cli)!: add foo\n\nbody\n\nBREAKING CHANGE: bar\nRefs: #1");
+        assert_eq!(ConventionalCommit::from_string(&commit.to_string()).unwrap(), commit);
+    }
+}
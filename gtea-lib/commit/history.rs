@@ -0,0 +1,140 @@
+use crate::prelude::*;
+
+use crate::exec::GitBackend;
+
+/// `git log` format of a `LogEntry`: fields split by unit separators,
+/// entries by record separators
+pub const LOG_FORMAT: &str = "%H%x1f%an%x1f%ae%x1f%at%x1f%B%x1e";
+
+/// a commit as read from `git log`
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogEntry {
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    /// author time, seconds since the unix epoch
+    pub time: u64,
+    pub message: String,
+}
+
+impl LogEntry {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// which commits `get_log` returns, besides the range
+#[derive(Debug, Default, Clone)]
+pub struct LogFilter {
+    pub author: Option<String>,
+    /// path globs, e.g. `gtea-lib/**`
+    pub paths: Vec<String>,
+    /// path globs of changes to ignore
+    pub exclude: Vec<String>,
+    /// dates in any format `git log --since` understands
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// include merge commits
+    pub merges: bool,
+}
+
+impl LogFilter {
+    /// `git log` options for the filter
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(author) = &self.author {
+            args.push(format!("--author={author}"));
+        }
+        if let Some(since) = &self.since {
+            args.push(format!("--since={since}"));
+        }
+        if let Some(until) = &self.until {
+            args.push(format!("--until={until}"));
+        }
+        if !self.merges {
+            args.push("--no-merges".to_string());
+        }
+        if !self.paths.is_empty() || !self.exclude.is_empty() {
+            args.push("--".to_string());
+            args.extend(self.paths.iter().map(|v| format!(":(glob){v}")));
+            args.extend(self.exclude.iter().map(|v| format!(":(glob,exclude){v}")));
+        }
+        args
+    }
+}
+
+/// parse `git log` output written with `LOG_FORMAT`
+pub fn parse_log(output: &str) -> Result<Vec<LogEntry>> {
+    output
+        .split('\x1e')
+        .map(|v| v.trim_start_matches('\n'))
+        .filter(|v| !v.is_empty())
+        .map(|entry| {
+            let fields: Vec<&str> = entry.splitn(5, '\x1f').collect();
+            let [hash, author, email, time, message] = fields[..] else {
+                return Err(make_err!(Parse, "unexpected git log entry `{entry}`"));
+            };
+            Ok(LogEntry {
+                hash: hash.to_string(),
+                author: author.to_string(),
+                email: email.to_string(),
+                time: time.parse().map_err(|_| make_err!(Parse, "invalid commit time `{time}`"))?,
+                message: message.trim_end().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// commits in *range* passing *filter*, newest first
+pub fn get_log(git: &dyn GitBackend, range: &str, filter: &LogFilter, pwd: &str) -> Result<Vec<LogEntry>> {
+    parse_log(&git.log(range, &filter.args(), LOG_FORMAT, pwd)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_log, LogEntry, LogFilter};
+
+    #[test]
+    fn log() {
+        let output = "1111\x1fa\x1fa@b\x1f10\x1ffeat: foo\n\nbody\n\x1e\n2222\x1fb\x1fb@c\x1f20\x1ffix: bar\n\x1e\n";
+        let entries = parse_log(output).unwrap();
+        assert_eq!(
+            entries[0],
+            LogEntry {
+                hash: "1111".to_string(),
+                author: "a".to_string(),
+                email: "a@b".to_string(),
+                time: 10,
+                message: "feat: foo\n\nbody".to_string(),
+            }
+        );
+        assert_eq!(entries[1].subject(), "fix: bar");
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn filter() {
+        assert_eq!(LogFilter::default().args(), vec!["--no-merges"]);
+        let filter = LogFilter {
+            author: Some("foo".to_string()),
+            paths: vec!["gtea-lib/**".to_string()],
+            exclude: vec!["gtea-lib/templates/**".to_string()],
+            since: Some("2024-01-01".to_string()),
+            until: None,
+            merges: true,
+        };
+        assert_eq!(
+            filter.args(),
+            vec![
+                "--author=foo",
+                "--since=2024-01-01",
+                "--",
+                ":(glob)gtea-lib/**",
+                ":(glob,exclude)gtea-lib/templates/**"
+            ]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+
+use crate::prelude::*;
+
+use crate::config::ConfigCommit;
+use crate::util::glob;
+
+use super::history::LogEntry;
+use super::{strip_comments, ConventionalCommit};
+
+/// a rule a commit message breaks
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(rule: &'static str, message: String) -> Violation {
+        Violation { rule, message }
+    }
+}
+
+/// violations of one commit in a range
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub hash: String,
+    pub subject: String,
+    pub violations: Vec<Violation>,
+}
+
+/// check every commit in *entries*, reporting only the ones breaking a rule
+pub fn check_log(entries: &[LogEntry], config: &ConfigCommit) -> Result<Vec<Report>> {
+    let mut reports = vec![];
+    for entry in entries {
+        let violations = check_message(&entry.message, config)?;
+        if !violations.is_empty() {
+            reports.push(Report {
+                hash: entry.hash.clone(),
+                subject: entry.subject().to_string(),
+                violations,
+            });
+        }
+    }
+    Ok(reports)
+}
+
+pub fn to_json(reports: &[Report]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(reports)?)
+}
+
+/// check *commit* against the `[commit]` rules in *config*
+pub fn check(commit: &ConventionalCommit, config: &ConfigCommit) -> Result<Vec<Violation>> {
+    let mut violations = vec![];
+    let kind = &commit.kind;
+    if config.get_type(kind).is_none() {
+        let message = match suggest_type(kind, config) {
+            Some(suggestion) => format!("unknown type `{kind}`, did you mean `{suggestion}`?"),
+            None => format!("unknown type `{kind}`."),
+        };
+        violations.push(Violation::new("type", message));
+    }
+    if let Some(scope) = &commit.scope {
+        if !is_allowed_scope(scope, config)? {
+            let allowed = config.allowed_scopes.join(", ");
+            violations.push(Violation::new(
+                "scope",
+                format!("scope `{scope}` is not allowed; expected one of {allowed}."),
+            ));
+        }
+    }
+    let length = commit.subject().chars().count();
+    let max = config.max_subject_length;
+    if max > 0 && length > max {
+        violations.push(Violation::new(
+            "subject-length",
+            format!("subject is {length} characters long, the limit is {max}."),
+        ));
+    }
+    let max = config.max_line_length;
+    // trailers and urls can't be wrapped, only the body is checked
+    let body = commit.body.as_deref().unwrap_or_default();
+    for (i, line) in body.lines().enumerate() {
+        let length = line.chars().count();
+        if max > 0 && length > max && !line.contains("://") {
+            // the body starts on line 3, after the subject and a blank line
+            let line = i + 3;
+            violations.push(Violation::new(
+                "line-length",
+                format!("line {line} is {length} characters long, the limit is {max}."),
+            ));
+        }
+    }
+    for token in &config.required_trailers {
+        if commit.footer(token).is_none() {
+            violations.push(Violation::new("trailer", format!("missing `{token}:` trailer.")));
+        }
+    }
+    Ok(violations)
+}
+
+/// check a raw commit *message*, e.g. from a `commit-msg` hook.
+///
+/// comments are ignored and messages generated by git itself (merges,
+/// reverts, fixups) are accepted as-is.
+pub fn check_message(message: &str, config: &ConfigCommit) -> Result<Vec<Violation>> {
+    let message = strip_comments(message);
+    let generated = ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "];
+    if message.is_empty() || generated.iter().any(|v| message.starts_with(v)) {
+        return Ok(vec![]);
+    }
+    match ConventionalCommit::from_string(&message) {
+        Ok(commit) => check(&commit, config),
+        Err(Error::Parse(err)) => Ok(vec![Violation::new("format", err)]),
+        Err(err) => Err(err),
+    }
+}
+
+/// like `check`, but fails on the first violation
+pub fn validate(commit: &ConventionalCommit, config: &ConfigCommit) -> Result<()> {
+    match check(commit, config)?.into_iter().next() {
+        Some(violation) => {
+            let message = violation.message;
+            Err(make_err!(Data, "{message}"))
+        }
+        None => Ok(()),
+    }
+}
+
+fn is_allowed_scope(scope: &str, config: &ConfigCommit) -> Result<bool> {
+    if config.allowed_scopes.is_empty() {
+        return Ok(true);
+    }
+    for pattern in &config.allowed_scopes {
+        if glob::matches(pattern, scope)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// closest configured type to *kind*, if any is near enough to be a typo
+pub fn suggest_type<'a>(kind: &str, config: &'a ConfigCommit) -> Option<&'a str> {
+    let kind = kind.to_lowercase();
+    config
+        .types
+        .iter()
+        .map(|v| (edit_distance(&kind, &v.name), v.name.as_str()))
+        .filter(|(distance, name)| *distance <= name.len().max(kind.len()).div_ceil(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// levenshtein distance between *a* and *b*
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(current)
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, check_message, edit_distance, suggest_type};
+    use crate::commit::ConventionalCommit;
+    use crate::config::ConfigCommit;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("fet", "feat"), 1);
+        assert_eq!(edit_distance("feat", "feat"), 0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn suggestion() {
+        let config = ConfigCommit::default();
+        assert_eq!(suggest_type("fet", &config), Some("feat"));
+        assert_eq!(suggest_type("fxi", &config), Some("fix"));
+        assert_eq!(suggest_type("Docs", &config), Some("docs"));
+        assert_eq!(suggest_type("banana", &config), None);
+    }
+
+    #[test]
+    fn rules() {
+        let mut config = ConfigCommit {
+            allowed_scopes: vec!["cli".to_string(), "lib-*".to_string()],
+            max_subject_length: 25,
+            ..Default::default()
+        };
+        config.scopes.insert("docs/**".to_string(), "docs".to_string());
+
+        let commit = ConventionalCommit::from_string("feat(lib-foo): add foo").unwrap();
+        assert!(check(&commit, &config).unwrap().is_empty());
+
+        let commit = ConventionalCommit::from_string("fet(core): add a longer subject").unwrap();
+        let rules: Vec<&str> = check(&commit, &config).unwrap().iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec!["type", "scope", "subject-length"]);
+
+        // mapped scopes still have to be allowed
+        let commit = ConventionalCommit::from_string("docs(docs): fix typo").unwrap();
+        let rules: Vec<&str> = check(&commit, &config).unwrap().iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec!["scope"]);
+
+        let config = ConfigCommit {
+            max_line_length: 10,
+            required_trailers: vec!["Signed-off-by".to_string()],
+            ..Default::default()
+        };
+        let commit = ConventionalCommit::from_string(
+            "fix: foo\n\nshort\na line that is too long\nhttps://example.com/long\n\nReviewed-by: someone long",
+        )
+        .unwrap();
+        let violations = check(&commit, &config).unwrap();
+        let rules: Vec<&str> = violations.iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec!["line-length", "trailer"]);
+        assert_eq!(violations[0].message, "line 4 is 23 characters long, the limit is 10.");
+    }
+
+    #[test]
+    fn message() {
+        let config = ConfigCommit::default();
+        let rules = |message: &str| -> Vec<&str> {
+            check_message(message, &config).unwrap().iter().map(|v| v.rule).collect()
+        };
+        assert!(rules("# only a comment\n").is_empty());
+        assert!(rules("Merge branch 'foo'").is_empty());
+        assert!(rules("fix: foo\n# comment\n").is_empty());
+        assert_eq!(rules("add foo\n"), vec!["format"]);
+        assert_eq!(rules("fet: add foo\n"), vec!["type"]);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod conventional;
+pub mod history;
+pub mod lint;
+pub mod scope;
+pub use self::conventional::{strip_comments, ConventionalCommit, Footer, BREAKING_CHANGE};
@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+
+use crate::prelude::*;
+
+use crate::config::ConfigCommit;
+use crate::util::glob;
+
+/// whether *path* matches *pattern*, a pattern without wildcards also
+/// matches everything below it as a directory.
+fn matches_path(pattern: &str, path: &str) -> Result<bool> {
+    if pattern.contains(['*', '?']) {
+        return glob::matches(pattern, path);
+    }
+    let dir = pattern.trim_end_matches('/');
+    Ok(path == dir || path.strip_prefix(dir).is_some_and(|v| v.starts_with('/')))
+}
+
+/// scope of the most specific `[commit.scopes]` pattern matching *path*
+fn path_scope<'a>(path: &str, config: &'a ConfigCommit) -> Result<Option<&'a str>> {
+    let mut found: Option<(&str, &str)> = None;
+    for (pattern, scope) in &config.scopes {
+        if matches_path(pattern, path)? && found.is_none_or(|(v, _)| pattern.len() > v.len()) {
+            found = Some((pattern, scope));
+        }
+    }
+    Ok(found.map(|(_, scope)| scope))
+}
+
+/// infer the commit scope from the changed *paths*.
+///
+/// paths without a matching pattern are ignored; paths spanning several
+/// scopes are a conflict.
+pub fn infer_scope(paths: &[String], config: &ConfigCommit) -> Result<Option<String>> {
+    let mut scopes = BTreeSet::new();
+    for path in paths {
+        if let Some(scope) = path_scope(path, config)? {
+            scopes.insert(scope);
+        }
+    }
+    match scopes.len() {
+        0 => Ok(None),
+        1 => Ok(scopes.into_iter().next().map(|v| v.to_string())),
+        _ => {
+            let scopes = scopes.into_iter().collect::<Vec<&str>>().join(", ");
+            Err(make_err!(
+                Conflict,
+                "staged files span several scopes ({scopes}); pass one with -s."
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::infer_scope;
+    use crate::config::ConfigCommit;
+
+    fn config() -> ConfigCommit {
+        let scopes = BTreeMap::from([
+            ("gtea-lib/**".to_string(), "lib".to_string()),
+            ("gtea-lib/worktree/**".to_string(), "worktree".to_string()),
+            ("src/cli/**".to_string(), "cli".to_string()),
+        ]);
+        ConfigCommit {
+            scopes,
+            ..Default::default()
+        }
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn single() {
+        let config = config();
+        let scope = infer_scope(&paths(&["gtea-lib/worktree/mod.rs", "README.md"]), &config).unwrap();
+        assert_eq!(scope.as_deref(), Some("worktree"));
+        let scope = infer_scope(&paths(&["gtea-lib/config.rs"]), &config).unwrap();
+        assert_eq!(scope.as_deref(), Some("lib"));
+        assert_eq!(infer_scope(&paths(&["README.md"]), &config).unwrap(), None);
+    }
+
+    #[test]
+    fn directory() {
+        let mut config = config();
+        config.scopes.insert("src/cli/git.rs".to_string(), "git".to_string());
+        config.scopes.insert("docs".to_string(), "docs".to_string());
+        let scope = infer_scope(&paths(&["docs/guide/setup.md"]), &config).unwrap();
+        assert_eq!(scope.as_deref(), Some("docs"));
+        let scope = infer_scope(&paths(&["src/cli/git.rs"]), &config).unwrap();
+        assert_eq!(scope.as_deref(), Some("git"));
+        assert_eq!(infer_scope(&paths(&["docs.md"]), &config).unwrap(), None);
+    }
+
+    #[test]
+    fn ambiguous() {
+        let config = config();
+        let err = infer_scope(&paths(&["src/cli/git.rs", "gtea-lib/worktree/mod.rs"]), &config).unwrap_err();
+        assert!(err.to_string().contains("cli, worktree"));
+    }
+}
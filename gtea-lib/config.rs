@@ -1,11 +1,15 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
 use crate::util::constants;
 use crate::util::filepath;
+use crate::version::Bump;
 
 #[derive(Debug)]
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConfigMain {
     pub branch: String,
 }
@@ -20,6 +24,7 @@ impl Default for ConfigMain {
 
 #[derive(Debug)]
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConfigNightly {
     pub branch: String,
     pub enable: bool,
@@ -36,6 +41,7 @@ impl Default for ConfigNightly {
 
 #[derive(Debug)]
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConfigFeature {
     pub prefix: String,
 }
@@ -48,30 +54,197 @@ impl Default for ConfigFeature {
     }
 }
 
+/// per-branch settings, keyed by branch name in `[branches."<name>"]`
+#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct ConfigBranch {
+    pub parent: String,
+}
+
+/// a commit type allowed in `[[commit.types]]`
+#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct ConfigCommitType {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// changelog section the commits are grouped under, defaults to *name*
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+}
+
+impl ConfigCommitType {
+    fn new(name: &str, description: &str, heading: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            heading: Some(heading.to_string()),
+        }
+    }
+    pub fn heading(&self) -> &str {
+        self.heading.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigCommit {
+    pub types: Vec<ConfigCommitType>,
+    /// allowed scopes, may contain glob patterns. empty allows any scope
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_scopes: Vec<String>,
+    pub max_subject_length: usize,
+    /// limit for body lines, 0 disables it. lines with a url are not checked
+    pub max_line_length: usize,
+    /// footers every commit must have, e.g. `Signed-off-by`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_trailers: Vec<String>,
+    /// path glob to scope, used when no scope is given
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub scopes: BTreeMap<String, String>,
+}
+
+impl ConfigCommit {
+    pub fn get_type(&self, name: &str) -> Option<&ConfigCommitType> {
+        self.types.iter().find(|v| v.name == name)
+    }
+}
+
+impl Default for ConfigCommit {
+    fn default() -> Self {
+        Self {
+            types: vec![
+                ConfigCommitType::new("feat", "a new feature", "Features"),
+                ConfigCommitType::new("fix", "a bug fix", "Bug Fixes"),
+                ConfigCommitType::new("perf", "a performance improvement", "Performance"),
+                ConfigCommitType::new("refactor", "a change that neither fixes a bug nor adds a feature", "Refactoring"),
+                ConfigCommitType::new("docs", "documentation only changes", "Documentation"),
+                ConfigCommitType::new("style", "formatting, no code change", "Style"),
+                ConfigCommitType::new("test", "adding or fixing tests", "Tests"),
+                ConfigCommitType::new("build", "build system or dependencies", "Build"),
+                ConfigCommitType::new("ci", "ci configuration", "CI"),
+                ConfigCommitType::new("chore", "other changes that don't modify src or tests", "Miscellaneous"),
+                ConfigCommitType::new("revert", "reverts a previous commit", "Reverts"),
+            ],
+            allowed_scopes: vec![],
+            max_subject_length: 72,
+            max_line_length: 100,
+            required_trailers: vec![],
+            scopes: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigChangelog {
+    /// built-in template name or path to a template file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigVersion {
+    /// commit type to version bump, overriding `feat` = minor and `fix` = patch
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub bump: BTreeMap<String, Bump>,
+}
+
+/// file with a version field, found with *pattern*
+#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct ConfigReleaseFile {
+    /// path relative to the repository root
+    pub path: String,
+    /// regex whose first capture group is the version
+    pub pattern: String,
+}
+
+/// independently versioned member of the repository
+#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct ConfigReleasePackage {
+    pub name: String,
+    /// directory relative to the repository root
+    pub path: String,
+}
+
+#[derive(Debug, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigRelease {
+    /// files to bump instead of the detected Cargo.toml and package.json
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<ConfigReleaseFile>,
+    /// version each package on its own, with `<package>@vX.Y.Z` tags
+    pub independent: bool,
+    /// packages to version instead of the Cargo workspace members
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<ConfigReleasePackage>,
+}
+
 #[derive(Debug, Default)]
 #[derive(Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
     pub main: ConfigMain,
+    #[serde(default)]
     pub nightly: ConfigNightly,
+    #[serde(default)]
     pub feature: ConfigFeature,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub branches: BTreeMap<String, ConfigBranch>,
+    #[serde(default)]
+    pub commit: ConfigCommit,
+    #[serde(default)]
+    pub changelog: ConfigChangelog,
+    #[serde(default)]
+    pub version: ConfigVersion,
+    #[serde(default)]
+    pub release: ConfigRelease,
 }
 
 impl Config {
     pub fn path() -> Result<String> {
         let dir = &constants::CWD;
-        let path = filepath::join(&dir, &*constants::CONFIG_NAME);
-        return Ok(path);
+        let path = filepath::join(dir, &constants::CONFIG_NAME);
+        Ok(path)
     }
     pub fn new() -> Result<Self> {
         let path = Self::path()?;
-        if let Some(content) = std::fs::read_to_string(path).ok() {
-            let config: Self = toml::from_str(&content).unwrap_or(Self::default());
-            Ok(config)
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            toml::from_str(&content).map_err(|err| make_err!(Parse, "couldn't parse {path}: {err}"))
         } else {
-            return Ok(Self::default());
+            Ok(Self::default())
         }
     }
     pub fn to_string(&self) -> Result<String> {
         toml::to_string(self).map_err(|_| make_err!(Parse, "couldn't create toml from string"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn partial() {
+        let config: Config = toml::from_str("[branches.\"feat/api\"]\nparent = \"feat/core\"\n").unwrap();
+        assert_eq!(config.main.branch, "main");
+        assert_eq!(config.branches["feat/api"].parent, "feat/core");
+        let config: Config = toml::from_str("[nightly]\nenable = true\n").unwrap();
+        assert_eq!(config.nightly.branch, "nightly");
+        assert!(config.nightly.enable);
+    }
+
+    #[test]
+    fn commit_type() {
+        let config: Config = toml::from_str("[[commit.types]]\nname = \"feat\"\n").unwrap();
+        assert_eq!(config.commit.types[0].description, "");
+        assert_eq!(config.commit.types[0].heading(), "feat");
+    }
+}
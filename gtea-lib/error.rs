@@ -21,6 +21,12 @@ pub enum Error {
   Data(String),
   Regex(String),
   Parse(String),
+  // external command exited unsuccessfully
+  Exec {
+    cmd: String,
+    code: Option<i32>,
+    stderr: String,
+  },
   #[default]
   Unexpected,
 }
@@ -35,12 +41,33 @@ impl fmt::Display for Error {
             Error::Data(s) => format!("Data: {s}"),
             Error::Regex(s) => format!("Regex: {s}"),
             Error::Parse(s) => format!("Parse: {s}"),
-            Error::Unexpected => format!("unexpected error"),
+            Error::Exec { cmd, code, stderr } => {
+                let code = match code {
+                    Some(code) => format!("exit code {code}"),
+                    None => "signal".to_string(),
+                };
+                if stderr.is_empty() {
+                    format!("Exec: `{cmd}` failed with {code}")
+                } else {
+                    format!("Exec: `{cmd}` failed with {code}\n{stderr}")
+                }
+            }
+            Error::Unexpected => "unexpected error".to_string(),
         };
         write!(f, "{}", msg)
     }
 }
 
+impl Error {
+    /// process exit code to report for this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Exec { code: Some(code), .. } if *code != 0 => *code,
+            _ => 1,
+        }
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(value: io::Error) -> Self {
         Self::IO(format!("io error {}", value.raw_os_error().unwrap()))
@@ -49,18 +76,30 @@ impl From<io::Error> for Error {
 
 impl From<regex::Error> for Error {
     fn from(value: regex::Error) -> Self {
-        Self::Regex(format!("regex error {}", value.to_string()))
+        Self::Regex(format!("regex error {}", value))
     }
 }
 
 impl From<nom::Err<nom::error::Error<&str>>> for Error {
     fn from(value: nom::Err<nom::error::Error<&str>>) -> Self {
-        Self::Parse(format!("parse error {}", value.to_string()))
+        Self::Parse(format!("parse error {}", value))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Data(format!("json error {}", value))
+    }
+}
+
+impl From<minijinja::Error> for Error {
+    fn from(value: minijinja::Error) -> Self {
+        Self::Parse(format!("template error {}", value))
     }
 }
 
 impl From<std::str::Utf8Error> for Error {
     fn from(value: std::str::Utf8Error) -> Self {
-        Self::Data(format!("utf8 error {}", value.to_string()))
+        Self::Data(format!("utf8 error {}", value))
     }
 }
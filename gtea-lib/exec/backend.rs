@@ -0,0 +1,371 @@
+use crate::prelude::*;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use crate::util::filepath;
+
+use super::GitCommand;
+
+/// Git operations used by the workflow logic.
+///
+/// every operation takes the directory to run in as *pwd*.
+pub trait GitBackend {
+    fn current_branch(&self, pwd: &str) -> Result<String>;
+    /// `git worktree list --porcelain` output
+    fn worktree_list(&self, pwd: &str) -> Result<String>;
+    fn local_changes(&self, pwd: &str) -> Result<bool>;
+    fn stash_push(&self, pwd: &str) -> Result<()>;
+    fn stash_pop(&self, pwd: &str) -> Result<()>;
+    fn rebase(&self, branch: &str, pwd: &str) -> Result<()>;
+    /// rebase the commits after *upstream* onto *branch*
+    fn rebase_onto(&self, branch: &str, upstream: &str, pwd: &str) -> Result<()>;
+    fn push(&self, remote: &str, branch: &str, pwd: &str) -> Result<()>;
+    /// full hash of *rev*
+    fn rev_parse(&self, rev: &str, pwd: &str) -> Result<String>;
+    /// `git log` output for *range* using the pretty *format*
+    ///
+    /// - *args*: extra options like `--author`, pathspecs last after `--`
+    fn log(&self, range: &str, args: &[String], format: &str, pwd: &str) -> Result<String>;
+    /// local branch names
+    fn branches(&self, pwd: &str) -> Result<Vec<String>>;
+    /// value of the git config *key*, if set
+    fn config_get(&self, key: &str, pwd: &str) -> Result<Option<String>>;
+    /// commits of *branch* not in *base* and commits of *base* not in *branch*
+    fn ahead_behind(&self, base: &str, branch: &str, pwd: &str) -> Result<(usize, usize)>;
+    /// absolute path to the git dir shared by all worktrees
+    fn git_dir(&self, pwd: &str) -> Result<String>;
+    fn rebase_in_progress(&self, pwd: &str) -> Result<bool>;
+    fn rebase_continue(&self, pwd: &str) -> Result<()>;
+    fn rebase_abort(&self, pwd: &str) -> Result<()>;
+    /// move HEAD to *rev*, keeping local changes
+    fn reset_keep(&self, rev: &str, pwd: &str) -> Result<()>;
+    /// stash commit hashes, newest first
+    fn stash_list(&self, pwd: &str) -> Result<Vec<String>>;
+    fn stash_apply(&self, rev: &str, pwd: &str) -> Result<()>;
+    fn update_ref(&self, gref: &str, target: &str, pwd: &str) -> Result<()>;
+    fn delete_ref(&self, gref: &str, pwd: &str) -> Result<()>;
+    /// (hash, refname) of every ref under *prefix*
+    fn refs(&self, prefix: &str, pwd: &str) -> Result<Vec<(String, String)>>;
+    /// tag names, highest version first
+    fn tags(&self, pwd: &str) -> Result<Vec<String>>;
+}
+
+/// Backend running the `git` executable.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Subprocess;
+
+impl GitBackend for Subprocess {
+    fn current_branch(&self, pwd: &str) -> Result<String> {
+        super::git_current_branch(pwd)
+    }
+    fn worktree_list(&self, pwd: &str) -> Result<String> {
+        super::git_worktree_list(pwd)
+    }
+    fn local_changes(&self, pwd: &str) -> Result<bool> {
+        super::git_local_changes(pwd)
+    }
+    fn stash_push(&self, pwd: &str) -> Result<()> {
+        super::git_stash_push(pwd)
+    }
+    fn stash_pop(&self, pwd: &str) -> Result<()> {
+        super::git_stash_pop(pwd)
+    }
+    fn rebase(&self, branch: &str, pwd: &str) -> Result<()> {
+        super::git_rebase(branch, pwd)
+    }
+    fn rebase_onto(&self, branch: &str, upstream: &str, pwd: &str) -> Result<()> {
+        super::git_rebase_onto(branch, upstream, pwd)
+    }
+    fn push(&self, remote: &str, branch: &str, pwd: &str) -> Result<()> {
+        GitCommand::new()
+            .args(["push", "-u", remote, &format!("heads/{branch}")])
+            .cwd(pwd)
+            .run()
+    }
+    fn rev_parse(&self, rev: &str, pwd: &str) -> Result<String> {
+        GitCommand::new()
+            .args(["rev-parse", "--verify", rev])
+            .cwd(pwd)
+            .output()
+            .map(|x| x.trim_end_matches('\n').to_string())
+    }
+    fn log(&self, range: &str, args: &[String], format: &str, pwd: &str) -> Result<String> {
+        GitCommand::new()
+            .args(["-c", "log.showSignature=false", "log"])
+            .arg(format!("--format={format}"))
+            .arg(range)
+            .args(args)
+            .cwd(pwd)
+            .output()
+    }
+    fn branches(&self, pwd: &str) -> Result<Vec<String>> {
+        let output = GitCommand::new()
+            .args(["for-each-ref", "--format=%(refname:short)", "refs/heads"])
+            .cwd(pwd)
+            .output()?;
+        Ok(output.lines().map(|v| v.to_string()).collect())
+    }
+    fn config_get(&self, key: &str, pwd: &str) -> Result<Option<String>> {
+        let output = GitCommand::new().args(["config", "--get", key]).cwd(pwd).output();
+        match output {
+            Ok(v) => Ok(Some(v.trim_end_matches('\n').to_string())),
+            // key is not set
+            Err(Error::Exec { code: Some(1), .. }) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+    fn ahead_behind(&self, base: &str, branch: &str, pwd: &str) -> Result<(usize, usize)> {
+        let output = GitCommand::new()
+            .args(["rev-list", "--left-right", "--count"])
+            .arg(format!("heads/{base}...heads/{branch}"))
+            .cwd(pwd)
+            .output()?;
+        parse_ahead_behind(&output)
+    }
+    fn git_dir(&self, pwd: &str) -> Result<String> {
+        GitCommand::new()
+            .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
+            .cwd(pwd)
+            .output()
+            .map(|x| x.trim_end_matches('\n').to_string())
+    }
+    fn rebase_in_progress(&self, pwd: &str) -> Result<bool> {
+        for dir in ["rebase-merge", "rebase-apply"] {
+            let path = GitCommand::new()
+                .args(["rev-parse", "--path-format=absolute", "--git-path", dir])
+                .cwd(pwd)
+                .output()?;
+            if filepath::exists(path.trim_end_matches('\n')) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    fn rebase_continue(&self, pwd: &str) -> Result<()> {
+        GitCommand::new()
+            .args(["rebase", "--continue"])
+            .env("GIT_EDITOR", "true")
+            .cwd(pwd)
+            .run()
+    }
+    fn rebase_abort(&self, pwd: &str) -> Result<()> {
+        GitCommand::new().args(["rebase", "--abort"]).cwd(pwd).run()
+    }
+    fn reset_keep(&self, rev: &str, pwd: &str) -> Result<()> {
+        GitCommand::new().args(["reset", "-q", "--keep", rev]).cwd(pwd).run()
+    }
+    fn stash_list(&self, pwd: &str) -> Result<Vec<String>> {
+        let output = GitCommand::new().args(["stash", "list", "--format=%H"]).cwd(pwd).output()?;
+        Ok(output.lines().map(|v| v.to_string()).collect())
+    }
+    fn stash_apply(&self, rev: &str, pwd: &str) -> Result<()> {
+        GitCommand::new().args(["stash", "apply", "-q", rev]).cwd(pwd).run()
+    }
+    fn update_ref(&self, gref: &str, target: &str, pwd: &str) -> Result<()> {
+        super::git_update_ref(gref, target, pwd)
+    }
+    fn delete_ref(&self, gref: &str, pwd: &str) -> Result<()> {
+        GitCommand::new().args(["update-ref", "-d", gref]).cwd(pwd).run()
+    }
+    fn refs(&self, prefix: &str, pwd: &str) -> Result<Vec<(String, String)>> {
+        let output = GitCommand::new()
+            .args(["for-each-ref", "--format=%(objectname) %(refname)", prefix])
+            .cwd(pwd)
+            .output()?;
+        Ok(parse_refs(&output))
+    }
+    fn tags(&self, pwd: &str) -> Result<Vec<String>> {
+        let output = GitCommand::new()
+            .args(["tag", "--list", "--sort=-v:refname"])
+            .cwd(pwd)
+            .output()?;
+        Ok(output.lines().map(|v| v.to_string()).collect())
+    }
+}
+
+/// parse `<hash> <refname>` lines
+fn parse_refs(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|v| v.split_once(' '))
+        .map(|(hash, gref)| (hash.to_string(), gref.to_string()))
+        .collect()
+}
+
+/// parse `git rev-list --left-right --count base...branch` output into
+/// (ahead, behind)
+fn parse_ahead_behind(output: &str) -> Result<(usize, usize)> {
+    let counts: Vec<usize> = output
+        .split_whitespace()
+        .map(|v| v.parse::<usize>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| make_err!(Parse, "unexpected rev-list output {output}"))?;
+    match counts[..] {
+        [behind, ahead] => Ok((ahead, behind)),
+        _ => Err(make_err!(Parse, "unexpected rev-list output {output}")),
+    }
+}
+
+/// Backend that records every call and replays scripted outputs.
+///
+/// calls are recorded as `"<operation> <args...>"`. outputs are queued per
+/// operation; an operation without a queued output returns an empty string,
+/// `false` or `()`.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    calls: RefCell<Vec<String>>,
+    outputs: RefCell<HashMap<String, VecDeque<Result<String>>>>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// queue *output* for the next call to *op*
+    pub fn script(&self, op: &str, output: &str) -> &Self {
+        self.queue(op, Ok(output.to_string()))
+    }
+    /// queue *err* for the next call to *op*
+    pub fn script_err(&self, op: &str, err: Error) -> &Self {
+        self.queue(op, Err(err))
+    }
+    fn queue(&self, op: &str, output: Result<String>) -> &Self {
+        self.outputs.borrow_mut().entry(op.to_string()).or_default().push_back(output);
+        self
+    }
+    /// recorded calls in order
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    fn call(&self, op: &str, args: &[&str]) -> Result<String> {
+        let mut call = vec![op];
+        call.extend_from_slice(args);
+        self.calls.borrow_mut().push(call.join(" "));
+        match self.outputs.borrow_mut().get_mut(op).and_then(|v| v.pop_front()) {
+            Some(output) => output,
+            None => Ok(String::new()),
+        }
+    }
+}
+
+impl GitBackend for MockBackend {
+    fn current_branch(&self, pwd: &str) -> Result<String> {
+        self.call("current_branch", &[pwd])
+    }
+    fn worktree_list(&self, pwd: &str) -> Result<String> {
+        self.call("worktree_list", &[pwd])
+    }
+    fn local_changes(&self, pwd: &str) -> Result<bool> {
+        self.call("local_changes", &[pwd]).map(|v| v == "true")
+    }
+    fn stash_push(&self, pwd: &str) -> Result<()> {
+        self.call("stash_push", &[pwd]).map(|_| ())
+    }
+    fn stash_pop(&self, pwd: &str) -> Result<()> {
+        self.call("stash_pop", &[pwd]).map(|_| ())
+    }
+    fn rebase(&self, branch: &str, pwd: &str) -> Result<()> {
+        self.call("rebase", &[branch, pwd]).map(|_| ())
+    }
+    fn rebase_onto(&self, branch: &str, upstream: &str, pwd: &str) -> Result<()> {
+        self.call("rebase_onto", &[branch, upstream, pwd]).map(|_| ())
+    }
+    fn push(&self, remote: &str, branch: &str, pwd: &str) -> Result<()> {
+        self.call("push", &[remote, branch, pwd]).map(|_| ())
+    }
+    fn rev_parse(&self, rev: &str, pwd: &str) -> Result<String> {
+        self.call("rev_parse", &[rev, pwd])
+    }
+    fn log(&self, range: &str, args: &[String], format: &str, pwd: &str) -> Result<String> {
+        let mut call = vec![range];
+        call.extend(args.iter().map(|v| v.as_str()));
+        call.extend([format, pwd]);
+        self.call("log", &call)
+    }
+    fn branches(&self, pwd: &str) -> Result<Vec<String>> {
+        let output = self.call("branches", &[pwd])?;
+        Ok(output.lines().map(|v| v.to_string()).collect())
+    }
+    fn config_get(&self, key: &str, pwd: &str) -> Result<Option<String>> {
+        let output = self.call("config_get", &[key, pwd])?;
+        Ok(Some(output).filter(|v| !v.is_empty()))
+    }
+    fn ahead_behind(&self, base: &str, branch: &str, pwd: &str) -> Result<(usize, usize)> {
+        let output = self.call("ahead_behind", &[base, branch, pwd])?;
+        if output.is_empty() {
+            return Ok((0, 0));
+        }
+        parse_ahead_behind(&output)
+    }
+    fn git_dir(&self, pwd: &str) -> Result<String> {
+        self.call("git_dir", &[pwd])
+    }
+    fn rebase_in_progress(&self, pwd: &str) -> Result<bool> {
+        self.call("rebase_in_progress", &[pwd]).map(|v| v == "true")
+    }
+    fn rebase_continue(&self, pwd: &str) -> Result<()> {
+        self.call("rebase_continue", &[pwd]).map(|_| ())
+    }
+    fn rebase_abort(&self, pwd: &str) -> Result<()> {
+        self.call("rebase_abort", &[pwd]).map(|_| ())
+    }
+    fn reset_keep(&self, rev: &str, pwd: &str) -> Result<()> {
+        self.call("reset_keep", &[rev, pwd]).map(|_| ())
+    }
+    fn stash_list(&self, pwd: &str) -> Result<Vec<String>> {
+        let output = self.call("stash_list", &[pwd])?;
+        Ok(output.lines().map(|v| v.to_string()).collect())
+    }
+    fn stash_apply(&self, rev: &str, pwd: &str) -> Result<()> {
+        self.call("stash_apply", &[rev, pwd]).map(|_| ())
+    }
+    fn update_ref(&self, gref: &str, target: &str, pwd: &str) -> Result<()> {
+        self.call("update_ref", &[gref, target, pwd]).map(|_| ())
+    }
+    fn delete_ref(&self, gref: &str, pwd: &str) -> Result<()> {
+        self.call("delete_ref", &[gref, pwd]).map(|_| ())
+    }
+    fn refs(&self, prefix: &str, pwd: &str) -> Result<Vec<(String, String)>> {
+        let output = self.call("refs", &[prefix, pwd])?;
+        Ok(parse_refs(&output))
+    }
+    fn tags(&self, pwd: &str) -> Result<Vec<String>> {
+        let output = self.call("tags", &[pwd])?;
+        Ok(output.lines().map(|v| v.to_string()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GitBackend, MockBackend};
+    use crate::prelude::*;
+
+    #[test]
+    fn ahead_behind() {
+        assert_eq!(super::parse_ahead_behind("3\t1\n").unwrap(), (1, 3));
+        assert!(super::parse_ahead_behind("3\n").is_err());
+    }
+
+    #[test]
+    fn mock() {
+        let git = MockBackend::new();
+        git.script("current_branch", "feat/foo")
+            .script("local_changes", "true")
+            .script_err("rebase", make_err!(Conflict, "conflict"));
+        assert_eq!(git.current_branch("/a").unwrap(), "feat/foo");
+        assert_eq!(git.current_branch("/a").unwrap(), "");
+        assert!(git.local_changes("/a").unwrap());
+        assert!(git.rebase("main", "/a").is_err());
+        assert_eq!(
+            git.calls(),
+            vec![
+                "current_branch /a",
+                "current_branch /a",
+                "local_changes /a",
+                "rebase main /a",
+            ]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use crate::prelude::*;
+use crate::util::constants;
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use super::run::{run, run_interactive, run_return_code, run_with_output};
+
+/// Builder for a git invocation.
+///
+/// Arguments are passed to the process as-is, without going through a shell.
+#[derive(Debug, Clone)]
+pub struct GitCommand {
+    program: String,
+    args: Vec<String>,
+    cwd: String,
+    envs: HashMap<String, String>,
+}
+
+impl Default for GitCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitCommand {
+    /// `git` in the current directory
+    pub fn new() -> Self {
+        Self::program("git")
+    }
+    /// any other program in the current directory, e.g. `gh`
+    pub fn program(program: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            args: vec![],
+            cwd: constants::CWD.to_string(),
+            envs: HashMap::new(),
+        }
+    }
+
+    pub fn arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(|v| v.into()));
+        self
+    }
+    pub fn cwd(mut self, pwd: &str) -> Self {
+        self.cwd = pwd.to_string();
+        self
+    }
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.envs.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        cmd.current_dir(&self.cwd);
+        cmd.envs(&self.envs);
+        cmd
+    }
+
+    /// run and stream output to stdout
+    pub fn run(&self) -> Result<()> {
+        debug!("$ {self}");
+        run(&mut self.command(), &self.to_string())
+    }
+    /// run and capture stdout
+    pub fn output(&self) -> Result<String> {
+        debug!("$ {self}");
+        run_with_output(&mut self.command(), &self.to_string())
+    }
+    /// run attached to the terminal, e.g. for an editor
+    pub fn interactive(&self) -> Result<()> {
+        debug!("$ {self}");
+        run_interactive(&mut self.command(), &self.to_string())
+    }
+    /// run and only report the exit code
+    pub fn status(&self) -> Result<i32> {
+        debug!("$ {self}");
+        run_return_code(&mut self.command(), &self.to_string())
+    }
+    /// run a check command: exit code 0 is `true`, 1 is `false` and anything
+    /// else is an error.
+    pub fn test(&self) -> Result<bool> {
+        match self.status()? {
+            0 => Ok(true),
+            1 => Ok(false),
+            code => Err(Error::Exec {
+                cmd: self.to_string(),
+                code: Some(code),
+                stderr: String::new(),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for GitCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                write!(f, " {:?}", arg)?;
+            } else {
+                write!(f, " {}", arg)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GitCommand;
+
+    #[test]
+    fn display() {
+        let cmd = GitCommand::new().args(["commit", "-m", "feat: add foo; rm -rf x"]);
+        assert_eq!(cmd.to_string(), "git commit -m \"feat: add foo; rm -rf x\"");
+    }
+
+    #[test]
+    fn output() {
+        let output = GitCommand::new().arg("--version").output().unwrap();
+        assert!(output.starts_with("git version"));
+    }
+
+    #[test]
+    fn test() {
+        let cmd = GitCommand::program("sh").args(["-c", "exit 1"]);
+        assert!(!cmd.test().unwrap());
+        let cmd = GitCommand::program("sh").args(["-c", "exit 2"]);
+        assert_eq!(cmd.test().unwrap_err().exit_code(), 2);
+    }
+}
@@ -1,56 +1,168 @@
 use crate::prelude::*;
-use crate::util::constants;
+use crate::util::filepath;
 
-use self::run::{run_one, run_one_return_code, run_with_output};
+pub use self::backend::{GitBackend, MockBackend, Subprocess};
+pub use self::command::GitCommand;
 
+mod backend;
+mod command;
 mod run;
 
 pub fn git_push(remote: &str, branch: &str) -> Result<()> {
-    run_one(format!("git push -u {remote} heads/{branch}"), &constants::CWD)
+    GitCommand::new()
+        .args(["push", "-u", remote, &format!("heads/{branch}")])
+        .run()
 }
 
 pub fn git_commit(msg: &str) -> Result<()> {
-    run_one(format!("git commit -m {msg}"), &constants::CWD)
-}
-
-pub fn git_changelog(arg: &str) -> Result<()> {
-    run_one(format!("git -c pager.show=false show --format=\" - %C(yellow)%h%C(reset) %<(80,trunc)%s\" -q {arg}"), &constants::CWD)
+    GitCommand::new().args(["commit", "-m", msg]).run()
 }
 
 pub fn git_show_ref(arg: &str) -> Result<()> {
-    git_rev_parse(format!("$(git show-ref -s {arg})").as_str())
+    let output = GitCommand::new().args(["show-ref", "-s", arg]).output()?;
+    let hash = output.lines().next().ok_or(make_err!(NotFound, "ref {arg} not found."))?;
+    git_rev_parse(hash)
 }
 pub fn git_rev_parse(arg: &str) -> Result<()> {
-    run_one(format!("git rev-parse --short {arg}"), &constants::CWD)
+    GitCommand::new().args(["rev-parse", "--short", arg]).run()
 }
 
 pub fn git_init() -> Result<()> {
-    run_one(format!("git init"), &constants::CWD)
+    GitCommand::new().arg("init").run()
 }
-pub fn git_clone(arg: &str) -> Result<()> {
-    run_one(format!("gh repo clone {arg}"), &constants::CWD)
+pub fn git_clone(url: &str, git_args: &[&str]) -> Result<()> {
+    GitCommand::program("gh")
+        .args(["repo", "clone", url, "--"])
+        .args(git_args.iter().copied())
+        .run()
 }
 pub fn git_create(name: &str) -> Result<()> {
-    run_one(format!("gh repo create {name} --public --source=. --remote=origin --push"), &constants::CWD)
+    GitCommand::program("gh")
+        .args(["repo", "create", name, "--public", "--source=.", "--remote=origin", "--push"])
+        .run()
 }
 
 pub fn git_current_branch(pwd: &str) -> Result<String> {
-    run_with_output(format!("git branch --show-current"), pwd).map(|x| x.trim_end_matches("\n").to_string())
+    GitCommand::new()
+        .args(["branch", "--show-current"])
+        .cwd(pwd)
+        .output()
+        .map(|x| x.trim_end_matches('\n').to_string())
+}
+/// fullpath to the root of the worktree containing *pwd*
+pub fn git_toplevel(pwd: &str) -> Result<String> {
+    GitCommand::new()
+        .args(["rev-parse", "--show-toplevel"])
+        .cwd(pwd)
+        .output()
+        .map(|x| x.trim_end_matches('\n').to_string())
 }
 pub fn git_worktree_list(pwd: &str) -> Result<String> {
-    run_with_output(format!("git worktree list --porcelain"), pwd)
+    GitCommand::new().args(["worktree", "list", "--porcelain"]).cwd(pwd).output()
 }
 
 pub fn git_local_changes(pwd: &str) -> Result<bool> {
-    let result = run_one_return_code(format!("test -z \"$(git diff)\" || exit 1"), pwd)?;
-    Ok(!result)
+    let clean = GitCommand::new().args(["diff", "--quiet"]).cwd(pwd).test()?;
+    Ok(!clean)
+}
+/// `git status --porcelain` lines: staged, unstaged and untracked changes
+pub fn git_status(pwd: &str) -> Result<Vec<String>> {
+    let output = GitCommand::new().args(["status", "--porcelain"]).cwd(pwd).output()?;
+    Ok(output.lines().map(|v| v.to_string()).collect())
 }
 pub fn git_stash_push(pwd: &str) -> Result<()> {
-    run_one(format!("git stash -a -u"), pwd)
+    GitCommand::new().args(["stash", "-a", "-u"]).cwd(pwd).run()
+}
+/// stash staged, unstaged and untracked changes with *message*
+pub fn git_stash_save(message: &str, pwd: &str) -> Result<()> {
+    GitCommand::new().args(["stash", "push", "-q", "-u", "-m", message]).cwd(pwd).run()
 }
 pub fn git_stash_pop(pwd: &str) -> Result<()> {
-    run_one(format!("git stash pop -q"), pwd)
+    GitCommand::new().args(["stash", "pop", "-q"]).cwd(pwd).run()
 }
 pub fn git_rebase(branch: &str, pwd: &str) -> Result<()> {
-    run_one(format!("git rebase heads/{branch}"), pwd)
+    GitCommand::new().arg("rebase").arg(format!("heads/{branch}")).cwd(pwd).run()
+}
+pub fn git_rebase_onto(branch: &str, upstream: &str, pwd: &str) -> Result<()> {
+    GitCommand::new()
+        .args(["rebase", "--onto", &format!("heads/{branch}"), upstream])
+        .cwd(pwd)
+        .run()
+}
+pub fn git_worktree_add(path: &str, branch: &str, start: &str, pwd: &str) -> Result<()> {
+    GitCommand::new()
+        .args(["worktree", "add", "--track", "-b", branch, path, start])
+        .cwd(pwd)
+        .run()
+}
+pub fn git_worktree_remove(path: &str, force: bool, pwd: &str) -> Result<()> {
+    let mut cmd = GitCommand::new().args(["worktree", "remove"]);
+    if force {
+        cmd = cmd.arg("--force");
+    }
+    cmd.arg(path).cwd(pwd).run()
+}
+pub fn git_worktree_prune(pwd: &str) -> Result<()> {
+    GitCommand::new().args(["worktree", "prune"]).cwd(pwd).run()
+}
+pub fn git_branch_delete(branch: &str, pwd: &str) -> Result<()> {
+    GitCommand::new().args(["branch", "-D", branch]).cwd(pwd).run()
+}
+pub fn git_update_ref(gref: &str, target: &str, pwd: &str) -> Result<()> {
+    GitCommand::new().args(["update-ref", gref, target]).cwd(pwd).run()
+}
+pub fn git_ref_exists(gref: &str, pwd: &str) -> Result<bool> {
+    GitCommand::new()
+        .args(["show-ref", "--verify", "--quiet", gref])
+        .cwd(pwd)
+        .test()
+}
+pub fn git_rev_list_count(range: &str, pwd: &str) -> Result<usize> {
+    let output = GitCommand::new().args(["rev-list", "--count", range]).cwd(pwd).output()?;
+    output.trim().parse::<usize>().map_err(|_| make_err!(Parse, "unexpected rev-list output for {range}"))
+}
+pub fn git_worktree_add_branch(path: &str, branch: &str, pwd: &str) -> Result<()> {
+    GitCommand::new()
+        .args(["worktree", "add", path, branch])
+        .cwd(pwd)
+        .run()
+}
+/// open *path* in `$EDITOR`
+pub fn edit_file(path: &str) -> Result<()> {
+    GitCommand::program("sh")
+        .args(["-c", "${EDITOR:-vi} \"$1\"", "sh", path])
+        .interactive()
+}
+pub fn git_merge_ff(branch: &str, pwd: &str) -> Result<()> {
+    GitCommand::new().args(["merge", "--ff-only", branch]).cwd(pwd).run()
+}
+/// cherry-pick *commit* with a `(cherry picked from commit ...)` line
+pub fn git_cherry_pick(commit: &str, pwd: &str) -> Result<()> {
+    GitCommand::new().args(["cherry-pick", "-x", commit]).cwd(pwd).output().map(|_| ())
+}
+pub fn git_cherry_pick_abort(pwd: &str) -> Result<()> {
+    GitCommand::new().args(["cherry-pick", "--abort"]).cwd(pwd).output().map(|_| ())
+}
+pub fn git_conflicted_files(pwd: &str) -> Result<Vec<String>> {
+    let output = GitCommand::new()
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .cwd(pwd)
+        .output()?;
+    Ok(output.lines().map(|v| v.to_string()).collect())
+}
+/// replace the message of the last commit, without running hooks
+pub fn git_commit_amend(msg: &str, pwd: &str) -> Result<()> {
+    GitCommand::new()
+        .args(["commit", "--amend", "--no-verify", "-q", "-m", msg])
+        .cwd(pwd)
+        .run()
+}
+pub fn git_staged_files(pwd: &str) -> Result<Vec<String>> {
+    let output = GitCommand::new().args(["diff", "--cached", "--name-only"]).cwd(pwd).output()?;
+    Ok(output.lines().map(|v| v.to_string()).collect())
+}
+/// directory git runs hooks from, honoring `core.hooksPath`
+pub fn git_hooks_dir(pwd: &str) -> Result<String> {
+    let output = GitCommand::new().args(["rev-parse", "--git-path", "hooks"]).cwd(pwd).output()?;
+    Ok(filepath::join(pwd, output.trim()))
 }
@@ -1,100 +1,125 @@
 use crate::prelude::*;
 
 use std::collections::HashMap;
-use std::io::{Read, stdout, Write, IoSlice};
+use std::io::{Read, stdout, Write};
 use std::process::{Command, Stdio};
-use std::thread;
+use std::process::ExitStatus;
+use std::thread::{self, JoinHandle};
+
+/// Number of stderr lines kept in an `Error::Exec`.
+const STDERR_TAIL: usize = 10;
 
 /// Pipe streams are blocking, we need separate threads to monitor them without blocking the primary thread.
-fn child_stream_to_vec<R>(mut stream: R) -> Result<()>
+///
+/// The streamed bytes are collected and returned when the thread is joined.
+fn child_stream_to_vec<R>(mut stream: R) -> Result<JoinHandle<Vec<u8>>>
 where
     R: Read + Send + 'static,
 {
     let out = stdout();
-    thread::Builder::new()
+    let handle = thread::Builder::new()
         .name("child_stream_to_vec".into())
-        .spawn(move || loop {
-            let mut buf = [0];
-            match stream.read(&mut buf) {
-                Err(err) => {
-                    println!("{}] Error reading from stream: {}", line!(), err);
-                    break;
-                }
-                Ok(got) => {
-                    if got == 0 {
-                        break;
-                    } else if got == 1 {
-                        let value = buf[0];
-                        out.lock().write_vectored(&[IoSlice::new(&[value])]).unwrap();
-                    } else {
-                        println!("{}] Unexpected number of bytes: {}", line!(), got);
+        .spawn(move || {
+            let mut vec = Vec::new();
+            loop {
+                let mut buf = [0];
+                match stream.read(&mut buf) {
+                    Err(err) => {
+                        println!("{}] Error reading from stream: {}", line!(), err);
                         break;
                     }
+                    Ok(got) => {
+                        if got == 0 {
+                            break;
+                        } else if got == 1 {
+                            let value = buf[0];
+                            vec.push(value);
+                            out.lock().write_all(&[value]).unwrap();
+                        } else {
+                            println!("{}] Unexpected number of bytes: {}", line!(), got);
+                            break;
+                        }
+                    }
                 }
             }
+            vec
         })?;
-    Ok(())
+    Ok(handle)
 }
 
-pub fn run(cmd: Vec<String>, pwd: &str) -> Result<()> {
-    let mut cwd: String = pwd.to_string();
-    for c in cmd {
-        let line = c.clone();
-        let elements = line.splitn(2, ' ');
-        let el: Vec<&str> = elements.collect();
-        if el[0] == "cd" {
-            cwd = el[1].to_string();
-            continue;
-        }
-        drop(el);
-        let cmd: &mut Command = &mut Command::new("bash");
-        cmd.arg("-c").arg(line);
-        cmd.current_dir(cwd.as_str());
-        cmd.envs(env_vars());
+/// Last `STDERR_TAIL` lines of *stderr*.
+fn stderr_tail(stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    let lines: Vec<&str> = stderr.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(STDERR_TAIL);
+    lines[start..].join("\n")
+}
 
-        let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
-        child_stream_to_vec(child.stdout.take().expect("!stdout"))?;
-        child_stream_to_vec(child.stderr.take().expect("!stderr"))?;
-        child.wait()?;
+/// Turn a finished command into `Error::Exec` when it did not succeed.
+fn check_status(cmd: &str, status: ExitStatus, stderr: &[u8]) -> Result<()> {
+    if status.success() {
+        return Ok(());
     }
-    Ok(())
+    Err(Error::Exec {
+        cmd: cmd.to_string(),
+        code: status.code(),
+        stderr: stderr_tail(stderr),
+    })
+}
+
+/// Run *cmd*, streaming its output to stdout.
+///
+/// - *line*: printable form of *cmd* used in errors
+pub fn run(cmd: &mut Command, line: &str) -> Result<()> {
+    cmd.envs(env_vars());
+
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let stdout = child_stream_to_vec(child.stdout.take().expect("!stdout"))?;
+    let stderr = child_stream_to_vec(child.stderr.take().expect("!stderr"))?;
+    let status = child.wait()?;
+    stdout.join().map_err(|_| Error::Unexpected)?;
+    let stderr = stderr.join().map_err(|_| Error::Unexpected)?;
+    check_status(line, status, &stderr)
 }
 
-pub fn run_one(cmd: String, pwd: &str) -> Result<()> {
-    run(vec![cmd], pwd)
+/// Run *cmd* with stdin, stdout and stderr inherited from gtea.
+///
+/// - *line*: printable form of *cmd* used in errors
+pub fn run_interactive(cmd: &mut Command, line: &str) -> Result<()> {
+    cmd.envs(env_vars());
+
+    let status = cmd.status()?;
+    check_status(line, status, &[])
 }
 
-pub fn run_one_return_code(cmd: String, pwd: &str) -> Result<bool> {
-    let cwd: String = pwd.to_string();
-    let line = cmd.clone();
-    let cmd: &mut Command = &mut Command::new("bash");
-    cmd.arg("-c").arg(line);
-    cmd.current_dir(cwd.as_str());
+/// Run *cmd* and return its exit code, so callers can tell apart expected
+/// non-zero codes from failures.
+///
+/// - *line*: printable form of *cmd* used in errors
+pub fn run_return_code(cmd: &mut Command, line: &str) -> Result<i32> {
     cmd.envs(env_vars());
 
-    let mut child = cmd.spawn()?;
-    let result = child.wait()?;
-    if !result.success() {
-        return Ok(false);
+    let output = cmd.output()?;
+    match output.status.code() {
+        Some(code) => Ok(code),
+        None => Err(Error::Exec {
+            cmd: line.to_string(),
+            code: None,
+            stderr: stderr_tail(&output.stderr),
+        }),
     }
-    Ok(true)
 }
 
-pub fn run_with_output(cmd: String, pwd: &str) -> Result<String> {
-    let cwd: String = pwd.to_string();
-    let line = cmd.clone();
-    let cmd: &mut Command = &mut Command::new("bash");
-    cmd.arg("-c").arg(line);
-    cmd.current_dir(cwd.as_str());
+/// Run *cmd* and return its captured stdout.
+///
+/// - *line*: printable form of *cmd* used in errors
+pub fn run_with_output(cmd: &mut Command, line: &str) -> Result<String> {
     cmd.envs(env_vars());
 
     let output = cmd.output()?;
+    check_status(line, output.status, &output.stderr)?;
 
-    let result = match output.status.success() {
-        true => std::str::from_utf8(&output.stdout),
-        false => std::str::from_utf8(&output.stderr),
-    };
-    match result {
+    match std::str::from_utf8(&output.stdout) {
         Ok(v) => Ok(v.to_string()),
         Err(err) => Err(err.into()),
     }
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::oplog::StashState;
+use crate::prelude::*;
+use crate::util::filepath;
+
+/// progress of a single journaled rebase
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepState {
+    Pending,
+    Rebasing,
+    Done,
+}
+
+/// rebase of *branch* onto *onto* in the worktree at *dir*
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct JournalStep {
+    pub branch: String,
+    pub onto: String,
+    pub dir: String,
+    pub state: StepState,
+    /// HEAD before the rebase started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// HEAD of *onto* before an earlier step rebased it. only the commits
+    /// after it are rebased
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// stash commit holding local changes of *dir*
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stash: Option<String>,
+    /// how *stash* was restored, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restored: Option<StashState>,
+}
+
+/// state of a multi-step workflow operation, stored in the git dir so that
+/// it can be continued or aborted after a conflict
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Journal {
+    pub operation: String,
+    /// branch to push to origin once all steps are done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<JournalStep>,
+}
+
+impl Journal {
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            push: None,
+            steps: vec![],
+        }
+    }
+    /// add a pending step
+    pub fn step(&mut self, branch: &str, onto: &str, dir: &str) {
+        self.steps.push(JournalStep {
+            branch: branch.to_string(),
+            onto: onto.to_string(),
+            dir: dir.to_string(),
+            state: StepState::Pending,
+            head: None,
+            upstream: None,
+            stash: None,
+            restored: None,
+        });
+    }
+
+    pub fn path(git_dir: &str) -> String {
+        filepath::join(&filepath::join(git_dir, "gtea"), "journal.toml")
+    }
+    /// load the journal of the operation in progress, if any
+    pub fn load(git_dir: &str) -> Result<Option<Self>> {
+        let path = Self::path(git_dir);
+        if !filepath::exists(&path) {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let journal = toml::from_str(&content).map_err(|_| make_err!(Parse, "couldn't parse journal"))?;
+        Ok(Some(journal))
+    }
+    pub fn save(&self, git_dir: &str) -> Result<()> {
+        let str = toml::to_string(self).map_err(|_| make_err!(Parse, "couldn't create toml from journal"))?;
+        std::fs::create_dir_all(filepath::join(git_dir, "gtea"))?;
+        std::fs::write(Self::path(git_dir), str)?;
+        Ok(())
+    }
+    pub fn remove(git_dir: &str) -> Result<()> {
+        let path = Self::path(git_dir);
+        if filepath::exists(&path) {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Journal, StepState};
+
+    #[test]
+    fn journal() {
+        let dir = std::env::temp_dir().join(format!("gtea-journal-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let mut journal = Journal::new("restack");
+        journal.push = Some("main".to_string());
+        journal.step("feat/a", "main", "/repo.git/a");
+        journal.steps[0].state = StepState::Rebasing;
+        journal.steps[0].head = Some("1111".to_string());
+        journal.step("feat/b", "feat/a", "/repo.git/b");
+        journal.save(dir).unwrap();
+        assert_eq!(Journal::load(dir).unwrap(), Some(journal));
+        Journal::remove(dir).unwrap();
+        assert_eq!(Journal::load(dir).unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
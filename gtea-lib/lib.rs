@@ -6,6 +6,14 @@ pub mod exec;
 pub mod config;
 pub mod data;
 pub mod worktree;
+pub mod journal;
+pub mod backup;
+pub mod oplog;
+pub mod commit;
+pub mod changelog;
+pub mod version;
+pub mod release;
+pub mod backport;
 
 extern crate log;
 extern crate lazy_static;
@@ -13,3 +21,5 @@ extern crate regex;
 extern crate serde;
 extern crate toml;
 extern crate nom;
+extern crate serde_json;
+extern crate minijinja;
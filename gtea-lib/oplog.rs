@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::util::{filepath, time};
+
+/// position of a branch before and after an operation. `None` means the
+/// branch did not exist.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct RefChange {
+    pub branch: String,
+    /// worktree the branch was checked out in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
+/// what became of a stash entry by the end of an operation
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StashState {
+    /// never restored, the changes are only in the stash list
+    Stashed,
+    /// popped back into the worktree
+    Popped,
+    /// applied to the worktree and left in the stash list. entries logged
+    /// before the state was recorded were always restored.
+    #[default]
+    Applied,
+}
+
+/// stash entry created in the worktree at *dir*
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct StashEntry {
+    pub dir: String,
+    pub hash: String,
+    #[serde(default)]
+    pub state: StashState,
+}
+
+/// a finished workflow command and every branch it moved
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Operation {
+    pub id: usize,
+    pub command: String,
+    /// seconds since the unix epoch
+    pub time: u64,
+    /// id of the operation this one undid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<usize>,
+    #[serde(default)]
+    pub refs: Vec<RefChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stashes: Vec<StashEntry>,
+}
+
+#[derive(Debug, Default)]
+#[derive(Serialize, Deserialize)]
+struct OpLog {
+    #[serde(default)]
+    operations: Vec<Operation>,
+}
+
+pub fn path(git_dir: &str) -> String {
+    filepath::join(&filepath::join(git_dir, "gtea"), "oplog.toml")
+}
+
+/// every logged operation, oldest first
+pub fn load(git_dir: &str) -> Result<Vec<Operation>> {
+    let path = path(git_dir);
+    if !filepath::exists(&path) {
+        return Ok(vec![]);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let log: OpLog = toml::from_str(&content).map_err(|_| make_err!(Parse, "couldn't parse operation log"))?;
+    Ok(log.operations)
+}
+
+/// append an operation to the log
+///
+/// the log is a list of toml tables, so entries are appended to the file
+/// without rewriting earlier ones.
+pub fn record(
+    git_dir: &str,
+    command: &str,
+    reverts: Option<usize>,
+    refs: Vec<RefChange>,
+    stashes: Vec<StashEntry>,
+) -> Result<Operation> {
+    let operation = Operation {
+        id: load(git_dir)?.len(),
+        command: command.to_string(),
+        time: time::now()?,
+        reverts,
+        refs,
+        stashes,
+    };
+    let log = OpLog {
+        operations: vec![operation.clone()],
+    };
+    let str = toml::to_string(&log).map_err(|_| make_err!(Parse, "couldn't create toml from operation"))?;
+    std::fs::create_dir_all(filepath::join(git_dir, "gtea"))?;
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path(git_dir))?;
+    std::io::Write::write_all(&mut file, format!("{str}\n").as_bytes())?;
+    Ok(operation)
+}
+
+/// latest operation that is not an undo and has not been undone
+pub fn last_undoable(operations: &[Operation]) -> Option<&Operation> {
+    operations
+        .iter()
+        .rev()
+        .filter(|v| v.reverts.is_none())
+        .find(|v| !operations.iter().any(|u| u.reverts == Some(v.id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RefChange, StashEntry, StashState};
+
+    #[test]
+    fn oplog() {
+        let dir = std::env::temp_dir().join(format!("gtea-oplog-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+        let change = RefChange {
+            branch: "feat/a".to_string(),
+            dir: Some("/repo.git/a".to_string()),
+            before: Some("1111".to_string()),
+            after: Some("2222".to_string()),
+        };
+        super::record(dir, "wf update", None, vec![change.clone()], vec![]).unwrap();
+        let stash = StashEntry {
+            dir: "/repo.git/a".to_string(),
+            hash: "aaaa".to_string(),
+            state: StashState::Stashed,
+        };
+        super::record(dir, "wf restack", None, vec![change.clone(), change.clone()], vec![stash.clone()]).unwrap();
+        super::record(dir, "undo", Some(1), vec![], vec![]).unwrap();
+        let operations = super::load(dir).unwrap();
+        assert_eq!(operations.len(), 3);
+        assert_eq!(operations[1].refs, vec![change.clone(), change]);
+        assert_eq!(operations[1].stashes, vec![stash]);
+        assert_eq!(super::last_undoable(&operations).map(|v| v.id), Some(0));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
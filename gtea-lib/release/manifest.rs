@@ -0,0 +1,235 @@
+use regex::Regex;
+
+use crate::prelude::*;
+
+/// set the `[package]` and `[workspace.package]` version of a Cargo.toml,
+/// keeping its formatting. `None` when it has no version of its own.
+pub fn cargo_set_version(content: &str, version: &str) -> Result<Option<String>> {
+    let version_re = Regex::new(r#"^(\s*version\s*=\s*)"[^"]*""#)?;
+    let mut table = String::new();
+    let mut found = false;
+    let mut out = String::new();
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            table = trimmed.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+        } else if table == "package" || table == "workspace.package" {
+            if let Some(caps) = version_re.captures(line) {
+                found = true;
+                out.push_str(&format!("{}\"{version}\"", &caps[1]));
+                out.push_str(&line[caps[0].len()..]);
+                continue;
+            }
+        }
+        out.push_str(line);
+    }
+    Ok(if found { Some(out) } else { None })
+}
+
+/// `package.name` of a Cargo.toml
+pub fn cargo_package_name(content: &str) -> Option<String> {
+    let value: toml::Value = toml::from_str(content).ok()?;
+    value.get("package")?.get("name")?.as_str().map(|v| v.to_string())
+}
+
+/// `name` of a package.json
+pub fn json_package_name(content: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    value.get("name")?.as_str().map(|v| v.to_string())
+}
+
+/// `workspace.members` of a Cargo.toml, as written
+pub fn cargo_members(content: &str) -> Result<Vec<String>> {
+    let value: toml::Value = toml::from_str(content).map_err(|_| make_err!(Parse, "couldn't parse Cargo.toml"))?;
+    let members = value
+        .get("workspace")
+        .and_then(|v| v.get("members"))
+        .and_then(|v| v.as_array())
+        .map(|v| v.iter().filter_map(|v| v.as_str()).map(|v| v.to_string()).collect())
+        .unwrap_or_default();
+    Ok(members)
+}
+
+/// set the version of package *name* in a Cargo.lock. `None` when it does
+/// not list the package.
+pub fn lock_set_version(content: &str, name: &str, version: &str) -> Option<String> {
+    let name_line = format!("name = \"{name}\"");
+    let mut in_package = false;
+    let mut found = false;
+    let mut out = String::new();
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed == "[[package]]" {
+            in_package = false;
+        } else if trimmed == name_line {
+            in_package = true;
+        } else if in_package && trimmed.starts_with("version = ") {
+            in_package = false;
+            found = true;
+            out.push_str(&format!("version = \"{version}\"\n"));
+            continue;
+        }
+        out.push_str(line);
+    }
+    if found {
+        Some(out)
+    } else {
+        None
+    }
+}
+
+/// *version* with the operator of the requirement *old*, like `^1.2`.
+/// `None` for requirements that are not a version, like `*` or
+/// `workspace:*`.
+fn requirement(old: &str, version: &str) -> Option<String> {
+    let operator = old.trim_start_matches(['^', '~', '=', '>', '<', ' ']);
+    if !operator.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}{version}", &old[..old.len() - operator.len()]))
+}
+
+fn is_dependency_table(table: &str) -> bool {
+    ["dependencies", "dev-dependencies", "build-dependencies"].contains(&table)
+}
+
+/// set the version requirement on dependency *name* in a Cargo.toml,
+/// keeping its operator. `None` when it has no versioned dependency on
+/// *name*.
+pub fn cargo_set_dependency(content: &str, name: &str, version: &str) -> Result<Option<String>> {
+    let entry_re = Regex::new(&format!(r#"^\s*"?{}"?\s*="#, regex::escape(name)))?;
+    let string_re = Regex::new(r#"^(\s*=\s*)"([^"]*)""#)?;
+    let field_re = Regex::new(r#"(version\s*=\s*)"([^"]*)""#)?;
+    // `[dependencies]` style table, or `[dependencies.<name>]`
+    let mut in_dependencies = false;
+    let mut in_entry = false;
+    let mut found = false;
+    let mut out = String::new();
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            let table = trimmed.trim_matches(|c| c == '[' || c == ']').trim();
+            let segments: Vec<&str> = table.split('.').map(|v| v.trim_matches('"')).collect();
+            in_dependencies = segments.last().is_some_and(|v| is_dependency_table(v));
+            in_entry = segments.len() > 1
+                && segments[segments.len() - 1] == name
+                && is_dependency_table(segments[segments.len() - 2]);
+            out.push_str(line);
+            continue;
+        }
+        let (prefix, rest) = match entry_re.find(line) {
+            Some(entry) if in_dependencies => line.split_at(entry.end() - 1),
+            _ if in_entry => ("", line),
+            _ => {
+                out.push_str(line);
+                continue;
+            }
+        };
+        let re = if in_entry || !string_re.is_match(rest) { &field_re } else { &string_re };
+        let replaced = re.captures(rest).and_then(|caps| {
+            let old = caps.get(2)?;
+            let new = requirement(old.as_str(), version)?;
+            Some(format!("{prefix}{}{new}{}", &rest[..old.start()], &rest[old.end()..]))
+        });
+        match replaced {
+            Some(line) => {
+                found = true;
+                out.push_str(&line);
+            }
+            None => out.push_str(line),
+        }
+    }
+    Ok(if found { Some(out) } else { None })
+}
+
+/// set the version requirement on dependency *name* in every dependency
+/// list of a package.json. `None` when it has no versioned dependency on
+/// *name*.
+pub fn json_set_dependency(content: &str, name: &str, version: &str) -> Result<Option<String>> {
+    let re = Regex::new(&format!(r#""{}"\s*:\s*"([^"]*)""#, regex::escape(name)))?;
+    let mut found = false;
+    let mut out = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(content) {
+        let Some(old) = caps.get(1) else {
+            continue;
+        };
+        if let Some(new) = requirement(old.as_str(), version) {
+            found = true;
+            out.push_str(&content[last..old.start()]);
+            out.push_str(&new);
+            last = old.end();
+        }
+    }
+    out.push_str(&content[last..]);
+    Ok(if found { Some(out) } else { None })
+}
+
+/// set the first `"version"` field of a package.json
+pub fn json_set_version(content: &str, version: &str) -> Result<Option<String>> {
+    pattern_set_version(content, r#""version"\s*:\s*"([^"]*)""#, version)
+}
+
+/// replace the first capture group of the first match of *pattern* with
+/// *version*. `None` when nothing matches.
+pub fn pattern_set_version(content: &str, pattern: &str, version: &str) -> Result<Option<String>> {
+    let re = Regex::new(pattern)?;
+    if re.captures_len() < 2 {
+        return Err(make_err!(Data, "pattern `{pattern}` has no capture group for the version."));
+    }
+    let Some(group) = re.captures(content).and_then(|v| v.get(1)) else {
+        return Ok(None);
+    };
+    Ok(Some(format!("{}{version}{}", &content[..group.start()], &content[group.end()..])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo() {
+        let content = "workspace = { members = [ \"lib\" ] }\n\n[package]\nname = \"foo\"\nversion = \"0.1.0\" # keep\n\n[dependencies]\nbar = { version = \"1.0\" }\nversion = \"2\"\n";
+        let expected = "workspace = { members = [ \"lib\" ] }\n\n[package]\nname = \"foo\"\nversion = \"0.2.0\" # keep\n\n[dependencies]\nbar = { version = \"1.0\" }\nversion = \"2\"\n";
+        assert_eq!(cargo_set_version(content, "0.2.0").unwrap().as_deref(), Some(expected));
+        assert_eq!(cargo_package_name(content).as_deref(), Some("foo"));
+        assert_eq!(cargo_members(content).unwrap(), vec!["lib".to_string()]);
+
+        let inherited = "[package]\nname = \"foo\"\nversion.workspace = true\n";
+        assert_eq!(cargo_set_version(inherited, "0.2.0").unwrap(), None);
+    }
+
+    #[test]
+    fn lock() {
+        let content = "[[package]]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"foobar\"\nversion = \"3.0.0\"\n";
+        let expected = "[[package]]\nname = \"foo\"\nversion = \"0.2.0\"\n\n[[package]]\nname = \"foobar\"\nversion = \"3.0.0\"\n";
+        assert_eq!(lock_set_version(content, "foo", "0.2.0").as_deref(), Some(expected));
+        assert_eq!(lock_set_version(content, "bar", "0.2.0"), None);
+    }
+
+    #[test]
+    fn pattern() {
+        let content = "{\n  \"name\": \"foo\",\n  \"version\": \"1.2.3\"\n}\n";
+        let expected = "{\n  \"name\": \"foo\",\n  \"version\": \"1.3.0\"\n}\n";
+        assert_eq!(json_set_version(content, "1.3.0").unwrap().as_deref(), Some(expected));
+        assert_eq!(
+            pattern_set_version("VERSION = '1.0.0'\n", r"VERSION = '(.*)'", "2.0.0").unwrap().as_deref(),
+            Some("VERSION = '2.0.0'\n")
+        );
+        assert_eq!(pattern_set_version("foo\n", r"VERSION = '(.*)'", "2.0.0").unwrap(), None);
+        assert!(pattern_set_version("foo\n", r"VERSION", "2.0.0").is_err());
+    }
+
+    #[test]
+    fn dependencies() {
+        let content = "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[dependencies]\nbar = { path = \"../bar\", version = \"^0.1\" }\nbarbaz = \"0.1\"\n\n[dev-dependencies]\nbar = \"=0.1.0\"\n\n[build-dependencies.bar]\npath = \"../bar\"\nversion = \"0.1.0\"\n";
+        let expected = "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[dependencies]\nbar = { path = \"../bar\", version = \"^0.2.0\" }\nbarbaz = \"0.1\"\n\n[dev-dependencies]\nbar = \"=0.2.0\"\n\n[build-dependencies.bar]\npath = \"../bar\"\nversion = \"0.2.0\"\n";
+        assert_eq!(cargo_set_dependency(content, "bar", "0.2.0").unwrap().as_deref(), Some(expected));
+        assert_eq!(cargo_set_dependency("[dependencies]\nbar = { path = \"../bar\" }\n", "bar", "0.2.0").unwrap(), None);
+
+        let content = "{\n  \"name\": \"app\",\n  \"dependencies\": { \"@foo/ui\": \"^1.0.0\" },\n  \"devDependencies\": { \"@foo/cli\": \"workspace:*\" }\n}\n";
+        let expected = "{\n  \"name\": \"app\",\n  \"dependencies\": { \"@foo/ui\": \"^1.1.0\" },\n  \"devDependencies\": { \"@foo/cli\": \"workspace:*\" }\n}\n";
+        assert_eq!(json_set_dependency(content, "@foo/ui", "1.1.0").unwrap().as_deref(), Some(expected));
+        assert_eq!(json_set_dependency(content, "@foo/cli", "1.1.0").unwrap(), None);
+    }
+}
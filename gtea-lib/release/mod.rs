@@ -0,0 +1,286 @@
+use std::str::FromStr;
+
+use crate::prelude::*;
+
+use crate::config::ConfigRelease;
+use crate::exec::GitCommand;
+use crate::util::{diff, filepath, glob};
+
+pub mod manifest;
+pub mod package;
+pub use self::package::Package;
+
+/// new content of a file in the release commit
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    /// path relative to the repository root
+    pub path: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl FileChange {
+    pub fn diff(&self) -> String {
+        diff::unified(&self.path, &self.before, &self.after)
+    }
+    pub fn write(&self, root: &str) -> Result<()> {
+        std::fs::write(filepath::join(root, &self.path), &self.after)?;
+        Ok(())
+    }
+}
+
+fn read(root: &str, path: &str) -> Result<Option<String>> {
+    let full = filepath::join(root, path);
+    if !filepath::exists(&full) {
+        return Ok(None);
+    }
+    Ok(Some(std::fs::read_to_string(full)?))
+}
+
+/// directories matching the workspace member *pattern*
+fn expand_member(root: &str, pattern: &str) -> Result<Vec<String>> {
+    let Some(wildcard) = pattern.find(['*', '?']) else {
+        return Ok(vec![pattern.to_string()]);
+    };
+    let prefix = match pattern[..wildcard].rfind('/') {
+        Some(i) => &pattern[..i],
+        None => "",
+    };
+    let mut members = vec![];
+    for entry in std::fs::read_dir(filepath::join(root, prefix))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let member = if prefix.is_empty() { name } else { format!("{prefix}/{name}") };
+        if glob::matches(pattern, &member)? {
+            members.push(member);
+        }
+    }
+    members.sort();
+    Ok(members)
+}
+
+/// apply *edit* to the pending content of *path*, read from the worktree at
+/// *root* on first use. `false` when the file does not exist.
+fn edit<F>(changes: &mut Vec<FileChange>, root: &str, path: &str, edit: F) -> Result<bool>
+where
+    F: FnOnce(&str) -> Result<Option<String>>,
+{
+    let i = match changes.iter().position(|v| v.path == path) {
+        Some(i) => i,
+        None => {
+            let Some(before) = read(root, path)? else {
+                return Ok(false);
+            };
+            changes.push(FileChange {
+                path: path.to_string(),
+                after: before.clone(),
+                before,
+            });
+            changes.len() - 1
+        }
+    };
+    if let Some(after) = edit(&changes[i].after)? {
+        changes[i].after = after;
+    }
+    Ok(true)
+}
+
+/// the root Cargo.toml and the manifests of its workspace members
+fn cargo_manifests(root: &str) -> Result<Vec<String>> {
+    let Some(content) = read(root, "Cargo.toml")? else {
+        return Ok(vec![]);
+    };
+    let mut paths = vec!["Cargo.toml".to_string()];
+    for pattern in manifest::cargo_members(&content)? {
+        for member in expand_member(root, &pattern)? {
+            paths.push(format!("{member}/Cargo.toml"));
+        }
+    }
+    Ok(paths)
+}
+
+/// set crate *name* to *version* in Cargo.lock and in the requirements of
+/// *manifests*
+fn cargo_dependents(
+    changes: &mut Vec<FileChange>,
+    root: &str,
+    manifests: &[String],
+    name: &str,
+    version: &str,
+) -> Result<()> {
+    for path in manifests {
+        edit(changes, root, path, |content| manifest::cargo_set_dependency(content, name, version))?;
+    }
+    edit(changes, root, "Cargo.lock", |content| Ok(manifest::lock_set_version(content, name, version)))?;
+    Ok(())
+}
+
+/// version fields of the root Cargo.toml, its workspace members and
+/// Cargo.lock, and the requirements between them
+fn cargo_changes(root: &str, version: &str) -> Result<Vec<FileChange>> {
+    let manifests = cargo_manifests(root)?;
+    let mut changes = vec![];
+    let mut names = vec![];
+    for path in &manifests {
+        edit(&mut changes, root, path, |content| {
+            names.extend(manifest::cargo_package_name(content));
+            manifest::cargo_set_version(content, version)
+        })?;
+    }
+    for name in &names {
+        cargo_dependents(&mut changes, root, &manifests, name, version)?;
+    }
+    Ok(changes)
+}
+
+/// changes setting every version field in the repository at *root* to
+/// *version*.
+///
+/// the files from the config are used when set, otherwise the Cargo
+/// workspace and package.json are detected.
+pub fn version_changes(root: &str, config: &ConfigRelease, version: &str) -> Result<Vec<FileChange>> {
+    let mut changes = vec![];
+    if !config.files.is_empty() {
+        for file in &config.files {
+            let path = &file.path;
+            let before = read(root, path)?.ok_or(make_err!(NotFound, "release file {path} not found."))?;
+            let pattern = &file.pattern;
+            let after = manifest::pattern_set_version(&before, pattern, version)?
+                .ok_or(make_err!(Data, "no version matching `{pattern}` in {path}."))?;
+            changes.push(FileChange {
+                path: path.to_string(),
+                before,
+                after,
+            });
+        }
+    } else {
+        changes.extend(cargo_changes(root, version)?);
+        edit(&mut changes, root, "package.json", |content| manifest::json_set_version(content, version))?;
+        if changes.is_empty() {
+            return Err(make_err!(
+                NotFound,
+                "no Cargo.toml or package.json in {root}; list the version files in [release.files]."
+            ));
+        }
+    }
+    changes.retain(|v| v.before != v.after);
+    Ok(changes)
+}
+
+/// changes setting each package of *releases* to its version, and the
+/// requirements on it in the manifests of *packages*
+pub fn package_changes(root: &str, packages: &[Package], releases: &[(Package, String)]) -> Result<Vec<FileChange>> {
+    let mut cargo = cargo_manifests(root)?;
+    for package in packages {
+        let path = package.file("Cargo.toml");
+        if !cargo.contains(&path) && filepath::exists(&filepath::join(root, &path)) {
+            cargo.push(path);
+        }
+    }
+    let json: Vec<String> = packages.iter().map(|v| v.file("package.json")).collect();
+
+    let mut changes = vec![];
+    for (package, version) in releases {
+        let mut name = package.name.clone();
+        let cargo_toml = package.file("Cargo.toml");
+        let package_json = package.file("package.json");
+        let is_cargo = edit(&mut changes, root, &cargo_toml, |content| {
+            name = manifest::cargo_package_name(content).unwrap_or(name.clone());
+            manifest::cargo_set_version(content, version)
+        })?;
+        if is_cargo {
+            cargo_dependents(&mut changes, root, &cargo, &name, version)?;
+            continue;
+        }
+        let is_json = edit(&mut changes, root, &package_json, |content| {
+            name = manifest::json_package_name(content).unwrap_or(name.clone());
+            manifest::json_set_version(content, version)
+        })?;
+        if is_json {
+            for path in &json {
+                edit(&mut changes, root, path, |content| manifest::json_set_dependency(content, &name, version))?;
+            }
+        }
+    }
+    changes.retain(|v| v.before != v.after);
+    Ok(changes)
+}
+
+/// subject of the release commit for *tags*
+pub fn commit_message(tags: &str) -> String {
+    format!("chore(release): {tags}")
+}
+
+/// kind of release; pre-release channels are named after their identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Stable,
+    Nightly,
+}
+
+impl Channel {
+    /// pre-release identifier of the channel
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Channel::Stable => None,
+            Channel::Nightly => Some("nightly"),
+        }
+    }
+}
+
+impl FromStr for Channel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Channel> {
+        match s {
+            "stable" => Ok(Channel::Stable),
+            "nightly" => Ok(Channel::Nightly),
+            _ => Err(make_err!(Parse, "unknown release channel `{s}`.")),
+        }
+    }
+}
+
+/// file changes and git commands of a release, so it can be shown before
+/// it runs
+#[derive(Debug)]
+pub struct Plan {
+    pub tags: Vec<String>,
+    pub changes: Vec<FileChange>,
+    pub steps: Vec<GitCommand>,
+}
+
+impl Plan {
+    /// commit *changes* in the worktree at *pwd*, then tag the commit with
+    /// each of *releases*: a tag and the notes for its message
+    pub fn new(releases: &[(String, String)], changes: Vec<FileChange>, pwd: &str) -> Plan {
+        let tags: Vec<String> = releases.iter().map(|v| v.0.clone()).collect();
+        let paths = changes.iter().map(|v| v.path.clone());
+        let mut steps = vec![
+            GitCommand::new().args(["add", "--"]).args(paths).cwd(pwd),
+            GitCommand::new().args(["commit", "-m", &commit_message(&tags.join(", "))]).cwd(pwd),
+        ];
+        for (tag, notes) in releases {
+            let message = format!("{tag}\n\n{notes}");
+            steps.push(
+                GitCommand::new()
+                    .args(["tag", "-a", tag, "--cleanup=verbatim", "-m", message.trim_end()])
+                    .cwd(pwd),
+            );
+        }
+        Plan { tags, changes, steps }
+    }
+
+    /// write the changes to the worktree at *root*, then commit and tag
+    pub fn run(&self, root: &str) -> Result<()> {
+        for change in &self.changes {
+            change.write(root)?;
+        }
+        for step in &self.steps {
+            step.run()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+
+use crate::prelude::*;
+
+use crate::commit::history::{self, LogEntry, LogFilter};
+use crate::commit::ConventionalCommit;
+use crate::config::{ConfigRelease, ConfigVersion};
+use crate::exec::GitBackend;
+use crate::version::{self, NextVersion, Version};
+
+use super::{cargo_manifests, manifest, read};
+
+/// an independently versioned member of the repository
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    /// directory relative to the repository root, empty for the root
+    pub path: String,
+}
+
+impl Package {
+    pub fn new(name: &str, path: &str) -> Package {
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        Package {
+            name: name.to_string(),
+            path: if path == "." { "" } else { path }.to_string(),
+        }
+    }
+
+    /// release tag of *version*: `<package>@vX.Y.Z`
+    pub fn tag(&self, version: &Version) -> String {
+        format!("{}@{}", self.name, version.tag())
+    }
+
+    /// path of *file* in the package, relative to the repository root
+    pub fn file(&self, file: &str) -> String {
+        if self.path.is_empty() {
+            file.to_string()
+        } else {
+            format!("{}/{file}", self.path)
+        }
+    }
+
+    /// whether *other* is nested in the directory of the package
+    fn contains(&self, other: &Package) -> bool {
+        other.path != self.path && (self.path.is_empty() || other.path.starts_with(&format!("{}/", self.path)))
+    }
+}
+
+/// packages from the config, or the Cargo workspace members of the
+/// repository at *root*
+pub fn discover(root: &str, config: &ConfigRelease) -> Result<Vec<Package>> {
+    if !config.packages.is_empty() {
+        return Ok(config.packages.iter().map(|v| Package::new(&v.name, &v.path)).collect());
+    }
+    let mut packages = vec![];
+    for path in cargo_manifests(root)? {
+        let Some(content) = read(root, &path)? else {
+            continue;
+        };
+        if let Some(name) = manifest::cargo_package_name(&content) {
+            let dir = path.trim_end_matches("Cargo.toml");
+            packages.push(Package::new(&name, dir));
+        }
+    }
+    if packages.is_empty() {
+        return Err(make_err!(NotFound, "no packages found; add them to [[release.packages]] in gtea.toml."));
+    }
+    Ok(packages)
+}
+
+/// commits in *range* that belong to *package*: they change its files
+/// outside of nested *packages*, or their scope is the package name
+pub fn get_log(
+    git: &dyn GitBackend,
+    package: &Package,
+    packages: &[Package],
+    range: &str,
+    pwd: &str,
+) -> Result<Vec<LogEntry>> {
+    let filter = LogFilter {
+        paths: match package.path.as_str() {
+            "" => vec![],
+            path => vec![format!("{path}/**")],
+        },
+        exclude: packages
+            .iter()
+            .filter(|v| package.contains(v))
+            .map(|v| format!("{}/**", v.path))
+            .collect(),
+        ..Default::default()
+    };
+    let changed: HashSet<String> = history::get_log(git, range, &filter, pwd)?.into_iter().map(|v| v.hash).collect();
+    let entries = history::get_log(git, range, &LogFilter::default(), pwd)?;
+    Ok(entries
+        .into_iter()
+        .filter(|entry| {
+            let scope = ConventionalCommit::from_string(&entry.message).ok().and_then(|v| v.scope);
+            changed.contains(&entry.hash) || scope.as_deref() == Some(package.name.as_str())
+        })
+        .collect())
+}
+
+/// compute the next stable version of *package* from its commits since its
+/// latest stable release tag
+pub fn next_version(
+    git: &dyn GitBackend,
+    package: &Package,
+    packages: &[Package],
+    config: &ConfigVersion,
+    pwd: &str,
+) -> Result<NextVersion> {
+    let current = version::get_package_versions(git, &package.name, pwd)?
+        .into_iter()
+        .find(|(_, v)| !v.is_prerelease());
+    let range = match &current {
+        Some((tag, _)) => format!("{tag}..HEAD"),
+        None => "HEAD".to_string(),
+    };
+    let entries = get_log(git, package, packages, &range, pwd)?;
+    Ok(NextVersion::new(current, entries, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_log, Package};
+    use crate::exec::MockBackend;
+
+    #[test]
+    fn package() {
+        let root = Package::new("gtea", ".");
+        let lib = Package::new("gtea-lib", "./gtea-lib/");
+        assert_eq!(root.path, "");
+        assert_eq!(lib.path, "gtea-lib");
+        assert_eq!(lib.file("CHANGELOG.md"), "gtea-lib/CHANGELOG.md");
+        assert_eq!(lib.tag(&crate::version::Version::new(0, 2, 0)), "gtea-lib@v0.2.0");
+        assert!(root.contains(&lib));
+        assert!(!lib.contains(&root));
+        assert!(!Package::new("a", "crates/a").contains(&Package::new("ab", "crates/ab")));
+    }
+
+    #[test]
+    fn log() {
+        let root = Package::new("gtea", "");
+        let lib = Package::new("gtea-lib", "gtea-lib");
+        let packages = vec![root.clone(), lib.clone()];
+        let git = MockBackend::new();
+        git.script("log", "2222\x1fa\x1fa@b\x1f5\x1ffix: bar\x1e")
+            .script("log", "1111\x1fa\x1fa@b\x1f10\x1ffeat(gtea-lib): foo\x1e\n2222\x1fa\x1fa@b\x1f5\x1ffix: bar\x1e\n3333\x1fa\x1fa@b\x1f1\x1fdocs: baz\x1e");
+        let entries = get_log(&git, &lib, &packages, "HEAD", "/repo").unwrap();
+        let hashes: Vec<&str> = entries.iter().map(|v| v.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["1111", "2222"]);
+        assert_eq!(git.calls()[0], "log HEAD --no-merges -- :(glob)gtea-lib/** %H%x1f%an%x1f%ae%x1f%at%x1f%B%x1e /repo");
+
+        git.script("log", "").script("log", "");
+        get_log(&git, &root, &packages, "HEAD", "/repo").unwrap();
+        assert_eq!(git.calls()[2], "log HEAD --no-merges -- :(glob,exclude)gtea-lib/** %H%x1f%an%x1f%ae%x1f%at%x1f%B%x1e /repo");
+    }
+}
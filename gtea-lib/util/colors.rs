@@ -7,3 +7,4 @@ pub static COLOR_BLUE: &str = "\x1b[34m";
 pub static COLOR_MAGENTA: &str = "\x1b[35m";
 pub static COLOR_CYAN: &str = "\x1b[36m";
 pub static COLOR_RESET: &str = "\x1b[0m";
+pub static COLOR_BOLD: &str = "\x1b[1m";
@@ -0,0 +1,140 @@
+/// lines of context around each change
+const CONTEXT: usize = 3;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Op<'a> {
+    Keep(&'a str),
+    Remove(&'a str),
+    Add(&'a str),
+}
+
+/// line edits turning *a* into *b*
+///
+/// the common prefix and suffix are kept as-is, so the longest common
+/// subsequence table only spans the changed middle part.
+fn ops<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let mut ops: Vec<Op> = a[..prefix].iter().map(|v| Op::Keep(v)).collect();
+    ops.extend(lcs_ops(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]));
+    ops.extend(a[a.len() - suffix..].iter().map(|v| Op::Keep(v)));
+    ops
+}
+
+/// line edits turning *a* into *b*, from their longest common subsequence
+fn lcs_ops<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<Op<'a>> {
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut ops = vec![];
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            ops.push(Op::Keep(a[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op::Remove(a[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Add(b[j]));
+            j += 1;
+        }
+    }
+    ops
+}
+
+/// unified diff of *before* and *after*, empty when they are equal
+pub fn unified(path: &str, before: &str, after: &str) -> String {
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+    let ops = ops(&a, &b);
+    let changed: Vec<usize> = (0..ops.len()).filter(|i| !matches!(ops[*i], Op::Keep(_))).collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // group changes whose context overlaps into hunks of op indices
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for i in changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    // line numbers before each op
+    let mut lines = vec![(1, 1)];
+    for op in &ops {
+        let (x, y) = *lines.last().unwrap_or(&(1, 1));
+        lines.push(match op {
+            Op::Keep(_) => (x + 1, y + 1),
+            Op::Remove(_) => (x + 1, y),
+            Op::Add(_) => (x, y + 1),
+        });
+    }
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        let old = hunk.iter().filter(|v| !matches!(v, Op::Add(_))).count();
+        let new = hunk.iter().filter(|v| !matches!(v, Op::Remove(_))).count();
+        let (x, y) = lines[start];
+        // empty ranges point at the line before, like diff does
+        let x = if old == 0 { x - 1 } else { x };
+        let y = if new == 0 { y - 1 } else { y };
+        out.push_str(&format!("@@ -{x},{old} +{y},{new} @@\n"));
+        for op in hunk {
+            match op {
+                Op::Keep(line) => out.push_str(&format!(" {line}\n")),
+                Op::Remove(line) => out.push_str(&format!("-{line}\n")),
+                Op::Add(line) => out.push_str(&format!("+{line}\n")),
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified;
+
+    #[test]
+    fn equal() {
+        assert_eq!(unified("a", "foo\nbar\n", "foo\nbar\n"), "");
+    }
+
+    #[test]
+    fn hunks() {
+        let before = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let after = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        let expected = "--- a/f\n+++ b/f\n@@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n@@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n";
+        assert_eq!(unified("f", before, after), expected);
+    }
+
+    #[test]
+    fn large() {
+        let before: String = (0..100_000).map(|v| format!("{v}\n")).collect();
+        let after = format!("new\n{before}");
+        assert_eq!(unified("f", &before, &after), "--- a/f\n+++ b/f\n@@ -1,3 +1,4 @@\n+new\n 0\n 1\n 2\n");
+    }
+
+    #[test]
+    fn new_file() {
+        assert_eq!(unified("f", "", "a\n"), "--- a/f\n+++ b/f\n@@ -0,0 +1,1 @@\n+a\n");
+    }
+}
@@ -52,7 +52,7 @@ pub fn is_dir(path: &str) -> bool {
 }
 
 pub fn extend(path: &str) -> Result<String> {
-    if path.len() == 0 {
+    if path.is_empty() {
         return Err(make_err!(Missing, "path not long enough"));
     }
 
@@ -62,7 +62,7 @@ pub fn extend(path: &str) -> Result<String> {
     }
 
     let cwd = &constants::CWD;
-    let extended = join(&cwd, path);
+    let extended = join(cwd, path);
     let abs_path = abs(&extended)?;
 
     Ok(abs_path)
@@ -76,3 +76,12 @@ pub fn base_name(path: &str) -> Result<String> {
         .ok_or(Error::Unexpected)?
         .to_string())
 }
+
+pub fn parent(path: &str) -> Result<String> {
+    Ok(Path::new(path)
+        .parent()
+        .ok_or(make_err!(NotFound, "path {path} has no parent"))?
+        .to_str()
+        .ok_or(Error::Unexpected)?
+        .to_string())
+}
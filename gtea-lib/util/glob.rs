@@ -0,0 +1,54 @@
+use crate::prelude::*;
+
+use regex::Regex;
+
+/// translate a glob *pattern* into an anchored regex.
+///
+/// `*` and `?` stay within one path segment, `**` matches across segments.
+fn to_regex(pattern: &str) -> Result<Regex> {
+    let mut re = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches zero directories
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Ok(Regex::new(&re)?)
+}
+
+/// whether *text* matches the glob *pattern*
+pub fn matches(pattern: &str, text: &str) -> Result<bool> {
+    Ok(to_regex(pattern)?.is_match(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn glob() {
+        assert!(matches("cli", "cli").unwrap());
+        assert!(!matches("cli", "clif").unwrap());
+        assert!(matches("lib-*", "lib-foo").unwrap());
+        assert!(matches("src/*", "src/main.rs").unwrap());
+        assert!(!matches("src/*", "src/cli/git.rs").unwrap());
+        assert!(matches("src/**", "src/cli/git.rs").unwrap());
+        assert!(matches("**/*.rs", "main.rs").unwrap());
+        assert!(matches("gtea-lib/**/mod.rs", "gtea-lib/exec/mod.rs").unwrap());
+        assert!(matches("a.?s", "a.rs").unwrap());
+        assert!(!matches("a.?s", "abrs").unwrap());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod constants;
 pub mod filepath;
-mod colors;
+pub(crate) mod colors;
 pub mod msg;
 pub mod io;
 pub mod url;
+pub mod time;
+pub mod glob;
+pub mod diff;
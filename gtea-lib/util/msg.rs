@@ -1,4 +1,4 @@
-use super::colors::{COLOR_BLUE, COLOR_CYAN, COLOR_MAGENTA, COLOR_RESET, COLOR_YELLOW};
+use super::colors::{COLOR_BLUE, COLOR_CYAN, COLOR_GREEN, COLOR_MAGENTA, COLOR_RED, COLOR_RESET, COLOR_YELLOW};
 
 pub mod general {
     use crate::util::msg::{COLOR_CYAN, COLOR_MAGENTA, COLOR_RESET, COLOR_YELLOW};
@@ -17,55 +17,162 @@ pub mod general {
 
 pub fn clone(name: &str, url: &str) {
     println!(
-        "{}",
-        format!(
-            "cloning {} from {} ...",
-            general::name_f(name),
-            general::url_f(url)
-        )
+        "cloning {} from {} ...",
+        general::name_f(name),
+        general::url_f(url)
     );
 }
 
 pub fn build(name: &str, path: &str) {
     println!(
-        "{}",
-        format!(
-            "building {} at {COLOR_BLUE}{}{COLOR_RESET} ...",
-            general::name_f(name),
-            path
-        )
+        "building {} at {COLOR_BLUE}{}{COLOR_RESET} ...",
+        general::name_f(name),
+        path
     );
 }
 
 pub fn fetch(name: &str, url: &str) {
     println!(
-        "{}",
-        format!(
-            "fetching {} from {} ...",
-            general::name_f(name),
-            general::url_f(url)
-        )
+        "fetching {} from {} ...",
+        general::name_f(name),
+        general::url_f(url)
     )
 }
 
 pub fn create_config(path: &str) {
     println!(
-        "{}",
-        format!(
-            "creating config file at {COLOR_BLUE}{}{COLOR_RESET} ...",
-            path
-        )
+        "creating config file at {COLOR_BLUE}{}{COLOR_RESET} ...",
+        path
     )
 }
 
 pub fn add(name: &str) {
-    println!("{}", format!("adding  {}", general::name_f(name)))
+    println!("adding  {}", general::name_f(name))
 }
 
 pub fn remove(name: &str) {
-    println!("{}", format!("removing  {}", general::name_f(name)))
+    println!("removing  {}", general::name_f(name))
 }
 
 pub fn add_flask(name: &str, url: &str) {
-    println!("{}", format!("adding flask {} from {}", general::name_f(name), general::url_f(url)))
+    println!("adding flask {} from {}", general::name_f(name), general::url_f(url))
+}
+
+pub fn create_worktree(branch: &str, path: &str) {
+    println!(
+        "creating worktree for {} at {COLOR_BLUE}{}{COLOR_RESET} ...",
+        general::name_f(branch),
+        path
+    )
+}
+
+pub fn backup(name: &str, gref: &str) {
+    println!("saving {} to {}", general::name_f(name), general::url_f(gref))
+}
+
+pub fn stash(name: &str, hash: &str) {
+    println!("stashing changes of {} as {}", general::name_f(name), general::url_f(hash))
+}
+
+pub fn rebase(name: &str, onto: &str) {
+    println!("rebasing {} onto {}", general::name_f(name), general::name_f(onto))
+}
+
+pub fn rollback(name: &str) {
+    println!("rolling back {}", general::name_f(name))
+}
+
+/// print a branch in a tree, with commits ahead of and behind its parent
+pub fn tree_node(prefix: &str, name: &str, counts: Option<(usize, usize)>) {
+    match counts {
+        Some((ahead, behind)) => println!(
+            "{prefix}{} {COLOR_GREEN}+{ahead}{COLOR_RESET} {COLOR_RED}-{behind}{COLOR_RESET}",
+            general::name_f(name)
+        ),
+        None => println!("{prefix}{}", general::name_f(name)),
+    }
+}
+
+pub fn backup_entry(name: &str, date: &str, time: u64, hash: &str) {
+    println!(
+        "{} {COLOR_BLUE}{date}{COLOR_RESET} {time} {}",
+        general::name_f(name),
+        general::url_f(&hash[..hash.len().min(7)])
+    )
+}
+
+pub fn restore(name: &str, gref: &str) {
+    println!("restored {} from {}", general::name_f(name), general::url_f(gref))
+}
+
+pub fn operation(id: usize, date: &str, command: &str, note: &str) {
+    println!("{COLOR_YELLOW}#{id}{COLOR_RESET} {COLOR_BLUE}{date}{COLOR_RESET} {command} {note}")
+}
+
+pub fn ref_change(name: &str, before: Option<&str>, after: Option<&str>) {
+    let short = |v: Option<&str>| match v {
+        Some(v) => v[..v.len().min(7)].to_string(),
+        None => "(none)".to_string(),
+    };
+    println!("    {} {} -> {}", general::name_f(name), short(before), short(after))
+}
+
+pub fn undo(command: &str) {
+    println!("undid {}", general::name_f(command))
+}
+
+pub fn hook(name: &str, state: &str, path: &str) {
+    println!("{} {state} at {COLOR_BLUE}{path}{COLOR_RESET}", general::name_f(name))
+}
+
+pub fn lint_commit(hash: &str, subject: &str) {
+    eprintln!("{} {subject}", general::url_f(&hash[..hash.len().min(7)]))
+}
+
+pub fn violation(rule: &str, message: &str) {
+    eprintln!("    {COLOR_RED}{rule}{COLOR_RESET}: {message}")
+}
+
+pub fn write_changelog(path: &str) {
+    println!("writing changelog to {COLOR_BLUE}{path}{COLOR_RESET}")
+}
+
+pub fn version_current(tag: &str, bump: &str) {
+    eprintln!("{} since {}", general::name_f(bump), general::url_f(tag))
+}
+
+pub fn version_commit(bump: &str, hash: &str, subject: &str) {
+    eprintln!("    {bump:<5} {} {subject}", general::url_f(hash))
+}
+
+pub fn diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{COLOR_BLUE}{line}{COLOR_RESET}")
+        } else if line.starts_with("@@") {
+            println!("{COLOR_CYAN}{line}{COLOR_RESET}")
+        } else if line.starts_with('+') {
+            println!("{COLOR_GREEN}{line}{COLOR_RESET}")
+        } else if line.starts_with('-') {
+            println!("{COLOR_RED}{line}{COLOR_RESET}")
+        } else {
+            println!("{line}")
+        }
+    }
+}
+
+pub fn dry_run(command: &str) {
+    println!("{COLOR_YELLOW}would run{COLOR_RESET} {command}")
+}
+
+pub fn release(tag: &str) {
+    println!("released {}", general::name_f(tag))
+}
+
+pub fn backport(branch: &str, width: usize, ok: bool, result: &str, detail: &str) {
+    let color = if ok { COLOR_GREEN } else { COLOR_RED };
+    println!(
+        "{} {color}{result:<8}{COLOR_RESET} {detail}",
+        general::name_f(&format!("{branch:<width$}"))
+    )
 }
@@ -0,0 +1,107 @@
+use crate::prelude::*;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// seconds since the unix epoch
+pub fn now() -> Result<u64> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| Error::Unexpected)?
+        .as_secs())
+}
+
+/// format *secs* since the unix epoch as `YYYY-MM-DD HH:MM:SS` in UTC
+pub fn format_utc(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let rem = secs % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// format *secs* since the unix epoch as `YYYY-MM-DD` in UTC
+pub fn format_date(secs: u64) -> String {
+    format_utc(secs)[..10].to_string()
+}
+
+/// format *secs* since the unix epoch as an RFC 2822 date in UTC, e.g.
+/// `Thu, 01 Jan 1970 00:00:00 +0000`
+pub fn format_rfc2822(secs: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let days = secs / 86400;
+    let (year, month, day) = civil_from_days(days as i64);
+    let rem = secs % 86400;
+    format!(
+        "{}, {day:02} {} {year:04} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[(days % 7) as usize],
+        MONTHS[month as usize - 1],
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// date of *days* since the unix epoch, see
+/// <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// parse a duration like `90s`, `30m`, `12h`, `7d` or `2w` into seconds.
+/// a plain number is taken as seconds.
+pub fn parse_duration(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+    let (value, unit) = input.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| make_err!(Parse, "invalid duration {input}"))?;
+    let unit = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(make_err!(Parse, "invalid duration unit in {input}")),
+    };
+    Ok(value * unit)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn format_utc() {
+        assert_eq!(super::format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(super::format_utc(951782400), "2000-02-29 00:00:00");
+        assert_eq!(super::format_utc(1792313593), "2026-10-18 08:53:13");
+    }
+
+    #[test]
+    fn format_rfc2822() {
+        assert_eq!(super::format_date(951782400), "2000-02-29");
+        assert_eq!(super::format_rfc2822(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+        assert_eq!(super::format_rfc2822(1792313593), "Sun, 18 Oct 2026 08:53:13 +0000");
+    }
+
+    #[test]
+    fn parse_duration() {
+        assert_eq!(super::parse_duration("90").unwrap(), 90);
+        assert_eq!(super::parse_duration("30m").unwrap(), 1800);
+        assert_eq!(super::parse_duration("2w").unwrap(), 1209600);
+        assert!(super::parse_duration("2y").is_err());
+        assert!(super::parse_duration("d").is_err());
+    }
+}
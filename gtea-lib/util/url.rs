@@ -7,7 +7,7 @@ pub fn extend_url(url: &str) -> Result<String> {
 
     let mut founddb = false;
 
-    for s in url.chars().into_iter() {
+    for s in url.chars() {
         if s == '.' {
             extended = format!("https://{}", url);
             return Ok(extended);
@@ -41,7 +41,7 @@ pub fn shorten_url(url: &str) -> Result<String> {
     //     .ok_or(make_err!(Missing, "url for flask is missing"))?;
     // let name = path::remove_extension(last.to_string());
 
-    let mut shortened = format!("{url}");
+    let mut shortened = url.to_string();
 
     let protocol_re = Regex::new(r"\w+://")?;
     let mut parts: Vec<&str> = protocol_re.split(&shortened).collect();
@@ -50,17 +50,11 @@ pub fn shorten_url(url: &str) -> Result<String> {
         shortened = parts.join("");
     }
 
-    match shortened.strip_prefix("github.com/") {
-        Some(s) => {
-            shortened = s.to_string();
-        }
-        None => {}
+    if let Some(s) = shortened.strip_prefix("github.com/") {
+        shortened = s.to_string();
     };
-    match shortened.strip_suffix(".git") {
-        Some(s) => {
-            shortened = s.to_string();
-        }
-        None => {}
+    if let Some(s) = shortened.strip_suffix(".git") {
+        shortened = s.to_string();
     };
 
     Ok(shortened)
@@ -75,6 +69,25 @@ pub fn url_name(url: &str) -> Result<String> {
     Ok(name)
 }
 
+/// browsable https url of a git *remote*, e.g. for compare links.
+///
+/// handles `https://host/owner/repo.git` and `git@host:owner/repo.git`.
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    if let Some(rest) = remote.strip_prefix("https://").or(remote.strip_prefix("http://")) {
+        return Some(format!("https://{rest}"));
+    }
+    let rest = remote.strip_prefix("ssh://").unwrap_or(remote);
+    let rest = rest.split_once('@').map(|v| v.1).unwrap_or(rest);
+    let (host, path) = rest.split_once(':').or(rest.split_once('/'))?;
+    // local paths have no host
+    if host.is_empty() || host.starts_with('.') || host.contains('/') {
+        return None;
+    }
+    Some(format!("https://{host}/{}", path.trim_start_matches('/')))
+}
+
 mod test {
     #[allow(unused_imports)]
     use crate::prelude::*;
@@ -131,6 +144,16 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn web_url() {
+        let expected = Some("https://github.com/comfysage/gtea".to_string());
+        assert_eq!(super::web_url("git@github.com:comfysage/gtea.git"), expected);
+        assert_eq!(super::web_url("https://github.com/comfysage/gtea.git"), expected);
+        assert_eq!(super::web_url("https://github.com/comfysage/gtea"), expected);
+        assert_eq!(super::web_url("ssh://git@github.com/comfysage/gtea.git"), expected);
+        assert_eq!(super::web_url("/tmp/repo.git"), None);
+    }
+
     #[test]
     fn url_name() -> Result<()> {
         assert_eq!(super::url_name("crispybaccoon/core")?, "crispybaccoon.core");
@@ -0,0 +1,365 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+use crate::commit::history::{self, LogEntry, LogFilter};
+use crate::commit::ConventionalCommit;
+use crate::config::ConfigVersion;
+use crate::exec::GitBackend;
+
+/// a semantic version like `1.2.3` or `1.2.3-nightly.4`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+
+    /// parse a version, with or without `v` prefix
+    pub fn parse(input: &str) -> Result<Version> {
+        let version = input.trim().trim_start_matches('v');
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return Err(make_err!(Parse, "invalid version `{input}`")),
+            None => (version, None),
+        };
+        let parts: Vec<u64> = core
+            .split('.')
+            .map(|v| v.parse::<u64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| make_err!(Parse, "invalid version `{input}`"))?;
+        let [major, minor, patch] = parts[..] else {
+            return Err(make_err!(Parse, "invalid version `{input}`"));
+        };
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some()
+    }
+
+    /// the version after applying *bump*.
+    ///
+    /// before 1.0.0 breaking changes only bump the minor version. a
+    /// pre-release bumps to its own release version when that is enough.
+    pub fn bump(&self, bump: Bump) -> Version {
+        let bump = match bump {
+            Bump::Major if self.major == 0 => Bump::Minor,
+            bump => bump,
+        };
+        if self.is_prerelease() {
+            let release = Version::new(self.major, self.minor, self.patch);
+            let enough = match bump {
+                Bump::None => true,
+                Bump::Patch => true,
+                Bump::Minor => self.patch == 0,
+                Bump::Major => self.patch == 0 && self.minor == 0,
+            };
+            if enough {
+                return release;
+            }
+        }
+        match bump {
+            Bump::None => self.clone(),
+            Bump::Patch => Version::new(self.major, self.minor, self.patch + 1),
+            Bump::Minor => Version::new(self.major, self.minor + 1, 0),
+            Bump::Major => Version::new(self.major + 1, 0, 0),
+        }
+    }
+
+    /// tag name of the version
+    pub fn tag(&self) -> String {
+        format!("v{self}")
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let core = (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch));
+        core.then_with(|| match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            // a pre-release comes before its release
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_pre(a, b),
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// compare dot separated pre-release identifiers, numbers numerically
+fn compare_pre(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// how much a commit moves the version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Bump::None => "none",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// bump caused by the commit *message*.
+///
+/// breaking changes are always major, other types follow `[version.bump]`
+/// with `feat` as minor and `fix` as patch by default.
+pub fn commit_bump(message: &str, config: &ConfigVersion) -> Bump {
+    let Ok(commit) = ConventionalCommit::from_string(message) else {
+        return Bump::None;
+    };
+    if commit.is_breaking() {
+        return Bump::Major;
+    }
+    if let Some(bump) = config.bump.get(&commit.kind) {
+        return *bump;
+    }
+    match commit.kind.as_str() {
+        "feat" => Bump::Minor,
+        "fix" => Bump::Patch,
+        _ => Bump::None,
+    }
+}
+
+/// release tags with their versions, highest first
+pub fn get_versions(git: &dyn GitBackend, pwd: &str) -> Result<Vec<(String, Version)>> {
+    let mut versions: Vec<(String, Version)> = git
+        .tags(pwd)?
+        .into_iter()
+        .filter_map(|tag| Version::parse(&tag).ok().map(|v| (tag, v)))
+        .collect();
+    versions.sort_by(|a, b| b.1.cmp(&a.1));
+    Ok(versions)
+}
+
+/// `<package>@v1.2.3` release tags of *package* with their versions,
+/// highest first
+pub fn get_package_versions(git: &dyn GitBackend, package: &str, pwd: &str) -> Result<Vec<(String, Version)>> {
+    let prefix = format!("{package}@");
+    let mut versions: Vec<(String, Version)> = git
+        .tags(pwd)?
+        .into_iter()
+        .filter_map(|tag| {
+            let version = Version::parse(tag.strip_prefix(&prefix)?).ok()?;
+            Some((tag, version))
+        })
+        .collect();
+    versions.sort_by(|a, b| b.1.cmp(&a.1));
+    Ok(versions)
+}
+
+/// the *channel* pre-release of *next*, numbered after the pre-releases of
+/// *next* in *versions*: `1.4.0-nightly.7`
+pub fn prerelease(next: &Version, channel: &str, versions: &[(String, Version)]) -> Version {
+    let prefix = format!("{channel}.");
+    let number = versions
+        .iter()
+        .map(|(_, v)| v)
+        .filter(|v| (v.major, v.minor, v.patch) == (next.major, next.minor, next.patch))
+        .filter_map(|v| v.pre.as_deref()?.strip_prefix(&prefix)?.parse::<u64>().ok())
+        .max()
+        .map_or(1, |v| v + 1);
+    Version {
+        pre: Some(format!("{prefix}{number}")),
+        ..Version::new(next.major, next.minor, next.patch)
+    }
+}
+
+/// the next release and what caused it
+#[derive(Debug)]
+pub struct NextVersion {
+    /// latest release tag and its version
+    pub current: Option<(String, Version)>,
+    pub next: Version,
+    pub bump: Bump,
+    /// commits since the current release that bump the version
+    pub commits: Vec<(Bump, LogEntry)>,
+}
+
+impl NextVersion {
+    /// the release after *current* with the changes in *entries*
+    pub fn new(current: Option<(String, Version)>, entries: Vec<LogEntry>, config: &ConfigVersion) -> NextVersion {
+        let commits: Vec<(Bump, LogEntry)> = entries
+            .into_iter()
+            .map(|v| (commit_bump(&v.message, config), v))
+            .filter(|(bump, _)| *bump != Bump::None)
+            .collect();
+        let bump = commits.iter().map(|v| v.0).max().unwrap_or(Bump::None);
+        let base = current.as_ref().map(|v| v.1.clone()).unwrap_or(Version::new(0, 0, 0));
+        NextVersion {
+            next: base.bump(bump),
+            current,
+            bump,
+            commits,
+        }
+    }
+
+    /// commits since the current release
+    pub fn range(&self) -> String {
+        match &self.current {
+            Some((tag, _)) => format!("{tag}..HEAD"),
+            None => "HEAD".to_string(),
+        }
+    }
+}
+
+/// compute the next stable version from the commits since the latest
+/// stable release tag
+pub fn next_version(git: &dyn GitBackend, config: &ConfigVersion, pwd: &str) -> Result<NextVersion> {
+    let current = get_versions(git, pwd)?.into_iter().find(|(_, v)| !v.is_prerelease());
+    let range = match &current {
+        Some((tag, _)) => format!("{tag}..HEAD"),
+        None => "HEAD".to_string(),
+    };
+    let entries = history::get_log(git, &range, &LogFilter::default(), pwd)?;
+    Ok(NextVersion::new(current, entries, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{commit_bump, next_version, prerelease, Bump, Version};
+    use crate::config::ConfigVersion;
+    use crate::exec::MockBackend;
+
+    fn v(input: &str) -> Version {
+        Version::parse(input).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(v("v1.2.3"), Version::new(1, 2, 3));
+        assert_eq!(v("1.2.3-nightly.7").pre.as_deref(), Some("nightly.7"));
+        assert_eq!(v("1.2.3-nightly.7").to_string(), "1.2.3-nightly.7");
+        assert!(Version::parse("1.2").is_err());
+        assert!(Version::parse("release").is_err());
+        assert!(Version::parse("1.2.3-").is_err());
+    }
+
+    #[test]
+    fn order() {
+        assert!(v("1.2.3") < v("1.10.0"));
+        assert!(v("1.0.0-nightly.2") < v("1.0.0"));
+        assert!(v("1.0.0-nightly.2") < v("1.0.0-nightly.10"));
+        assert!(v("1.0.0-alpha") < v("1.0.0-beta"));
+    }
+
+    #[test]
+    fn bump() {
+        assert_eq!(v("1.2.3").bump(Bump::Major), v("2.0.0"));
+        assert_eq!(v("1.2.3").bump(Bump::Minor), v("1.3.0"));
+        assert_eq!(v("1.2.3").bump(Bump::Patch), v("1.2.4"));
+        assert_eq!(v("1.2.3").bump(Bump::None), v("1.2.3"));
+        // 0.x
+        assert_eq!(v("0.2.3").bump(Bump::Major), v("0.3.0"));
+        assert_eq!(v("0.2.3").bump(Bump::Minor), v("0.3.0"));
+        // pre-releases
+        assert_eq!(v("1.3.0-rc.1").bump(Bump::Minor), v("1.3.0"));
+        assert_eq!(v("1.3.1-rc.1").bump(Bump::Minor), v("1.4.0"));
+    }
+
+    #[test]
+    fn commit() {
+        let mut config = ConfigVersion::default();
+        assert_eq!(commit_bump("feat: foo", &config), Bump::Minor);
+        assert_eq!(commit_bump("fix(cli)!: foo", &config), Bump::Major);
+        assert_eq!(commit_bump("docs: foo\n\nBREAKING CHANGE: bar", &config), Bump::Major);
+        assert_eq!(commit_bump("perf: foo", &config), Bump::None);
+        assert_eq!(commit_bump("not conventional", &config), Bump::None);
+        config.bump = BTreeMap::from([("perf".to_string(), Bump::Patch), ("feat".to_string(), Bump::Patch)]);
+        assert_eq!(commit_bump("perf: foo", &config), Bump::Patch);
+        assert_eq!(commit_bump("feat: foo", &config), Bump::Patch);
+    }
+
+    #[test]
+    fn next() {
+        let git = MockBackend::new();
+        git.script("tags", "v2.0.0-nightly.1\nv1.2.3\nv1.10.0\nfoo")
+            .script("log", "1111\x1fa\x1fa@b\x1f10\x1ffix: foo\x1e\n2222\x1fa\x1fa@b\x1f5\x1ffeat: bar\x1e\n3333\x1fa\x1fa@b\x1f1\x1fchore: baz\x1e");
+        let next = next_version(&git, &ConfigVersion::default(), "/repo").unwrap();
+        assert_eq!(next.current.as_ref().map(|v| v.0.as_str()), Some("v1.10.0"));
+        assert_eq!(next.next, v("1.11.0"));
+        assert_eq!(next.bump, Bump::Minor);
+        assert_eq!(next.commits.len(), 2);
+        assert_eq!(git.calls()[1], "log v1.10.0..HEAD --no-merges %H%x1f%an%x1f%ae%x1f%at%x1f%B%x1e /repo");
+    }
+
+    #[test]
+    fn nightly() {
+        let versions: Vec<(String, Version)> = ["1.4.0-nightly.6", "1.4.0-nightly.2", "1.3.0-nightly.9", "1.4.0-rc.8", "1.3.0"]
+            .iter()
+            .map(|v| (format!("v{v}"), Version::parse(v).unwrap()))
+            .collect();
+        assert_eq!(prerelease(&v("1.4.0"), "nightly", &versions), v("1.4.0-nightly.7"));
+        assert_eq!(prerelease(&v("1.5.0"), "nightly", &versions), v("1.5.0-nightly.1"));
+    }
+}
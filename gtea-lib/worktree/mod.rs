@@ -1,11 +1,12 @@
 use crate::prelude::*;
 
-use crate::exec;
+use crate::exec::GitBackend;
 
+#[allow(clippy::module_inception)]
 pub mod worktree;
 use self::worktree::Worktree;
 
-pub fn get_worktree_list(pwd: &str) -> Result<Vec<Worktree>> {
-    let output = exec::git_worktree_list(pwd)?;
+pub fn get_worktree_list(git: &dyn GitBackend, pwd: &str) -> Result<Vec<Worktree>> {
+    let output = git.worktree_list(pwd)?;
     Worktree::from_string(&output)
 }
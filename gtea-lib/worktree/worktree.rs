@@ -56,8 +56,7 @@ impl Worktree {
                 },
             }
         }).collect();
-        let result = result.into_iter().collect();
-        result
+        result.into_iter().collect()
     }
 
     fn parse_field<'a>(input: &'a str, field: &str) -> IResult<&'a str, String> {
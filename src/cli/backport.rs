@@ -0,0 +1,120 @@
+use gtea_lib::backport::{self, Outcome};
+use gtea_lib::commit::history::{self, LogFilter};
+use gtea_lib::exec::{self, GitBackend, Subprocess};
+use gtea_lib::oplog::{self, RefChange};
+use gtea_lib::prelude::*;
+use gtea_lib::util::{constants, filepath, msg};
+
+use crate::cli::workflow;
+
+/// directory for a new worktree of *branch*: in the bare root when there is
+/// one, otherwise next to the main worktree
+fn new_worktree_dir(git: &dyn GitBackend, branch: &str) -> Result<String> {
+    let name = branch.replace('/', "-");
+    if let Ok(root) = workflow::get_bare_root() {
+        return Ok(filepath::join(&root, &name));
+    }
+    let git_dir = git.git_dir(&constants::CWD)?;
+    let top = git_dir.strip_suffix("/.git").unwrap_or(&git_dir);
+    Ok(format!("{top}-{name}"))
+}
+
+/// worktree of *branch*, created when the branch is not checked out
+fn get_worktree(git: &dyn GitBackend, branch: &str) -> Result<String> {
+    if let Ok(dir) = workflow::get_branch_dir(git, branch) {
+        return Ok(dir);
+    }
+    let dir = new_worktree_dir(git, branch)?;
+    if filepath::exists(&dir) {
+        return Err(make_err!(Conflict, "directory {dir} already exists."));
+    }
+    msg::create_worktree(branch, &dir);
+    exec::git_worktree_add_branch(&dir, branch, &constants::CWD)?;
+    Ok(dir)
+}
+
+/// cherry-pick *commits* onto the worktree at *dir*, all or nothing
+fn pick(git: &dyn GitBackend, commits: &[String], dir: &str) -> Result<Outcome> {
+    if exec::git_local_changes(dir)? || !exec::git_staged_files(dir)?.is_empty() {
+        return Ok(Outcome::Failed("worktree has local changes".to_string()));
+    }
+    let start = git.rev_parse("HEAD", dir)?;
+    for commit in commits {
+        if let Err(err) = exec::git_cherry_pick(commit, dir) {
+            let files = exec::git_conflicted_files(dir)?;
+            let _ = exec::git_cherry_pick_abort(dir);
+            git.reset_keep(&start, dir)?;
+            if files.is_empty() {
+                return Ok(Outcome::failed(&err));
+            }
+            return Ok(Outcome::Conflict {
+                commit: commit[..commit.len().min(7)].to_string(),
+                files,
+            });
+        }
+        let filter = LogFilter {
+            merges: true,
+            ..Default::default()
+        };
+        let picked = history::get_log(git, "HEAD^!", &filter, dir)?;
+        if let Some(picked) = picked.first() {
+            exec::git_commit_amend(&backport::backport_message(&picked.message, commit), dir)?;
+        }
+    }
+    Ok(Outcome::Picked(commits.len()))
+}
+
+/// cherry-pick *commits*, in the given order, onto each of *targets* in
+/// their own worktree and print which targets succeeded.
+///
+/// a target with a conflict is left as it was.
+pub fn backport(commits: Vec<&String>, targets: Vec<&String>) -> Result<()> {
+    let git = Subprocess;
+    let commits: Vec<String> = commits
+        .iter()
+        .map(|v| git.rev_parse(&format!("{v}^{{commit}}"), &constants::CWD))
+        .collect::<Result<_>>()?;
+
+    let mut outcomes = vec![];
+    let mut changes = vec![];
+    for branch in &targets {
+        let outcome = match get_worktree(&git, branch) {
+            Ok(dir) => {
+                let before = git.rev_parse("HEAD", &dir)?;
+                let outcome = pick(&git, &commits, &dir)?;
+                if outcome.is_ok() {
+                    changes.push(RefChange {
+                        branch: branch.to_string(),
+                        after: Some(git.rev_parse("HEAD", &dir)?),
+                        dir: Some(dir),
+                        before: Some(before),
+                    });
+                }
+                outcome
+            }
+            Err(err) => Outcome::failed(&err),
+        };
+        outcomes.push((branch, outcome));
+    }
+    if !changes.is_empty() {
+        oplog::record(&git.git_dir(&constants::CWD)?, "backport", None, changes, vec![])?;
+    }
+
+    let width = targets.iter().map(|v| v.len()).max().unwrap_or_default();
+    for (branch, outcome) in &outcomes {
+        match outcome {
+            Outcome::Picked(1) => msg::backport(branch, width, true, "picked", "1 commit"),
+            Outcome::Picked(n) => msg::backport(branch, width, true, "picked", &format!("{n} commits")),
+            Outcome::Conflict { commit, files } => {
+                msg::backport(branch, width, false, "conflict", &format!("{commit}: {}", files.join(", ")))
+            }
+            Outcome::Failed(reason) => msg::backport(branch, width, false, "failed", reason),
+        }
+    }
+    let failed = outcomes.iter().filter(|(_, v)| !v.is_ok()).count();
+    if failed > 0 {
+        let total = outcomes.len();
+        return Err(make_err!(Conflict, "backport failed for {failed} of {total} targets."));
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use gtea_lib::backup::{self, Backup};
+use gtea_lib::exec::{GitBackend, Subprocess};
+use gtea_lib::prelude::*;
+use gtea_lib::util::{constants, msg, time};
+
+use crate::cli::workflow;
+
+/// show saved backups, optionally only of *branch*
+pub fn list(branch: Option<&str>) -> Result<()> {
+    let backups = backup::list(&Subprocess, branch, &constants::CWD)?;
+    for backup in backups {
+        msg::backup_entry(&backup.branch, &time::format_utc(backup.time), backup.time, &backup.hash);
+    }
+    Ok(())
+}
+/// move *branch* back to a backup.
+///
+/// - *when*: time of the backup as shown by `backup list`, defaults to the
+///   latest backup
+///
+/// the current position of *branch* is backed up first, so a restore can be
+/// undone as well.
+pub fn restore(branch: &str, when: Option<&str>) -> Result<()> {
+    let git = Subprocess;
+    let backups = backup::list(&git, Some(branch), &constants::CWD)?;
+    let target: &Backup = match when {
+        Some(when) => {
+            let time: u64 = when
+                .parse()
+                .map_err(|_| make_err!(Parse, "invalid backup time {when}."))?;
+            backups.iter().find(|v| v.time == time)
+        }
+        None => backups.last(),
+    }
+    .ok_or(make_err!(NotFound, "no backup of {branch} found."))?;
+    let head = format!("refs/heads/{branch}");
+    match workflow::get_branch_dir(&git, branch) {
+        Ok(dir) => {
+            if git.local_changes(&dir)? {
+                return Err(make_err!(Conflict, "worktree for {branch} has local changes."));
+            }
+            backup::save(&git, branch, &head, &dir)?;
+            git.reset_keep(&target.hash, &dir)?;
+        }
+        Err(_) => {
+            // the branch might have been deleted
+            if git.rev_parse(&head, &constants::CWD).is_ok() {
+                backup::save(&git, branch, &head, &constants::CWD)?;
+            }
+            git.update_ref(&head, &target.hash, &constants::CWD)?;
+        }
+    }
+    msg::restore(branch, &target.gref());
+    Ok(())
+}
+/// delete backups older than *older_than*, e.g. `30d`
+pub fn prune(older_than: &str) -> Result<()> {
+    let git = Subprocess;
+    let cutoff = time::now()?.saturating_sub(time::parse_duration(older_than)?);
+    for backup in backup::list(&git, None, &constants::CWD)? {
+        if backup.time < cutoff {
+            debug!("deleting {}", backup.gref());
+            backup::delete(&git, &backup, &constants::CWD)?;
+        }
+    }
+    Ok(())
+}
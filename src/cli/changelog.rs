@@ -0,0 +1,259 @@
+use gtea_lib::changelog::file::{ChangelogFile, Section, FILE_NAME, UNRELEASED};
+use gtea_lib::changelog::template::{self, Context};
+use gtea_lib::changelog::{self, Changelog, Filter, Format};
+use gtea_lib::config::Config;
+use gtea_lib::exec::{GitBackend, Subprocess};
+use gtea_lib::prelude::*;
+use gtea_lib::release::FileChange;
+use gtea_lib::util::{constants, filepath, msg, time, url};
+
+pub fn range_for_n(n: &str) -> String {
+    format!("HEAD~{n}..HEAD")
+}
+pub fn range_between(start: &str, end: &str) -> String {
+    format!("{start}..{end}")
+}
+pub fn range_since(hash: &str) -> String {
+    format!("{hash}..@@{{0}}")
+}
+
+/// print the commits in *range* grouped by type.
+///
+/// without *format* the changelog template from *template* or the config is
+/// used, falling back to terminal output.
+///
+/// - *version*: release version passed to the template
+pub fn show(
+    range: &str,
+    format: Option<Format>,
+    template: Option<&str>,
+    version: Option<&str>,
+    filter: &Filter,
+) -> Result<()> {
+    debug!("changelog between '{range}'");
+    let git = Subprocess;
+    let config = Config::new()?;
+    let changelog = changelog::get_changelog(&git, range, filter, &config.commit, &constants::CWD)?;
+    let template = template.or(config.changelog.template.as_deref());
+    let output = match (format, template) {
+        (None, Some(template)) => {
+            let source = template::load(template, &constants::CWD)?;
+            let context = Context::new(&changelog, &project_name(&git)?, version, time::now()?);
+            template::render(&source, &context)?
+        }
+        (format, _) => changelog::render::render(&changelog, format.unwrap_or(Format::Terminal))?,
+    };
+    println!("{output}");
+    Ok(())
+}
+
+/// name of the repository, from its git dir
+fn project_name(git: &dyn GitBackend) -> Result<String> {
+    let git_dir = git.git_dir(&constants::CWD)?;
+    let dir = match git_dir.strip_suffix("/.git") {
+        Some(dir) => dir.to_string(),
+        None => git_dir,
+    };
+    let name = filepath::base_name(&dir)?;
+    Ok(name.trim_end_matches(".git").to_string())
+}
+
+/// render the commits in *range* as a changelog section body
+fn section_body(
+    git: &dyn GitBackend,
+    config: &Config,
+    range: &str,
+    filter: &Filter,
+    pwd: &str,
+) -> Result<(String, Option<u64>)> {
+    let changelog = changelog::get_changelog(git, range, filter, &config.commit, pwd)?;
+    Ok((changelog::render::render(&changelog, Format::Markdown)?, newest(&changelog)))
+}
+
+/// time of the newest commit in *changelog*
+pub fn newest(changelog: &Changelog) -> Option<u64> {
+    changelog.groups.iter().flat_map(|v| &v.entries).map(|v| v.time).max()
+}
+
+/// commits since the latest release tag
+fn unreleased_range(tags: &[String]) -> String {
+    match tags.first() {
+        Some(tag) => format!("{tag}..HEAD"),
+        None => "HEAD".to_string(),
+    }
+}
+
+/// `YYYY-MM-DD` of *secs* since the unix epoch
+fn date(secs: u64) -> String {
+    time::format_date(secs)
+}
+
+/// url of the repository on the web, for compare links
+fn web_url(git: &dyn GitBackend, pwd: &str) -> Result<Option<String>> {
+    let remote = git.config_get("remote.origin.url", pwd)?;
+    Ok(remote.and_then(|v| url::web_url(&v)))
+}
+
+/// link of the changes from *prev* to *tag*
+fn compare_link(web: &str, prev: Option<&String>, tag: &str) -> String {
+    match prev {
+        Some(prev) => format!("{web}/compare/{prev}...{tag}"),
+        None => format!("{web}/releases/tag/{tag}"),
+    }
+}
+
+fn load_file(path: &str) -> Result<ChangelogFile> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => ChangelogFile::parse(&content),
+        Err(_) => Ok(ChangelogFile::default()),
+    }
+}
+
+/// CHANGELOG.md of the worktree at *pwd* with the changes in *range* added,
+/// as *version* or as the unreleased section.
+///
+/// - *range*: defaults to the commits since the latest of *tags*
+/// - *tags*: earlier release tags, highest first
+pub fn file_change(
+    git: &dyn GitBackend,
+    config: &Config,
+    range: Option<&str>,
+    version: Option<&str>,
+    filter: &Filter,
+    tags: &[String],
+    pwd: &str,
+) -> Result<FileChange> {
+    let range = range.map(|v| v.to_string()).unwrap_or(unreleased_range(tags));
+    let (body, newest) = section_body(git, config, &range, filter, pwd)?;
+    let release = version.map(|v| {
+        let version = v.trim_start_matches('v');
+        (version.to_string(), format!("v{version}"))
+    });
+    let release = release.as_ref().map(|(v, t)| (v.as_str(), t.as_str()));
+    add_section(git, FILE_NAME, &body, release, newest, tags, pwd)
+}
+
+/// the changelog at *path* in the worktree at *pwd* with *body* added, as
+/// the section of *release* or as the unreleased section. unchanged when
+/// *body* is empty.
+///
+/// - *release*: version and its tag
+/// - *newest*: time of the newest commit in *body*, the date of a new
+///   release section. an existing section keeps its date
+/// - *tags*: earlier release tags, highest first, for the compare links
+pub fn add_section(
+    git: &dyn GitBackend,
+    path: &str,
+    body: &str,
+    release: Option<(&str, &str)>,
+    newest: Option<u64>,
+    tags: &[String],
+    pwd: &str,
+) -> Result<FileChange> {
+    let full = filepath::join(pwd, path);
+    let before = std::fs::read_to_string(&full).unwrap_or_default();
+    if body.trim().is_empty() {
+        return Ok(FileChange {
+            path: path.to_string(),
+            after: before.clone(),
+            before,
+        });
+    }
+    let mut file = load_file(&full)?;
+    let web = web_url(git, pwd)?;
+    match release {
+        Some((version, tag)) => {
+            let existing = file.sections.iter().find(|v| v.version == version).and_then(|v| v.date.clone());
+            let date = match (existing, newest) {
+                (Some(existing), _) => existing,
+                (None, Some(newest)) => date(newest),
+                (None, None) => date(time::now()?),
+            };
+            file.insert(Section::new(version, Some(&date), body));
+            if let Some(web) = &web {
+                file.set_link(version, &compare_link(web, tags.first(), tag));
+                file.set_link(UNRELEASED, &format!("{web}/compare/{tag}...HEAD"));
+            }
+        }
+        None => {
+            file.insert(Section::new(UNRELEASED, None, body));
+            if let (Some(web), Some(tag)) = (&web, tags.first()) {
+                file.set_link(UNRELEASED, &format!("{web}/compare/{tag}...HEAD"));
+            }
+        }
+    }
+    Ok(FileChange {
+        path: path.to_string(),
+        before,
+        after: file.to_string(),
+    })
+}
+
+/// add the changes in *range* to CHANGELOG.md, as *version* or as the
+/// unreleased section.
+///
+/// - *range*: defaults to the commits since the latest release tag
+pub fn write(range: Option<&str>, version: Option<&str>, filter: &Filter) -> Result<()> {
+    let config = Config::new()?;
+    let git = Subprocess;
+    let tags = changelog::release_tags(&git, &constants::CWD)?;
+    let change = file_change(&git, &config, range, version, filter, &tags, &constants::CWD)?;
+    if change.before == change.after {
+        info!("no changes to add to {}.", change.path);
+        return Ok(());
+    }
+    change.write(&constants::CWD)?;
+    msg::write_changelog(&filepath::join(&constants::CWD, &change.path));
+    Ok(())
+}
+
+/// regenerate CHANGELOG.md from all release tags, keeping its header
+pub fn rebuild(filter: &Filter) -> Result<()> {
+    let git = Subprocess;
+    let config = Config::new()?;
+    let tags = changelog::release_tags(&git, &constants::CWD)?;
+    let path = filepath::join(&constants::CWD, FILE_NAME);
+    let old = load_file(&path)?;
+    let web = web_url(&git, &constants::CWD)?;
+
+    let mut file = ChangelogFile {
+        header: old.header,
+        ..Default::default()
+    };
+    let (body, _) = section_body(&git, &config, &unreleased_range(&tags), filter, &constants::CWD)?;
+    if !body.is_empty() {
+        file.sections.push(Section::new(UNRELEASED, None, &body));
+    }
+    if let (Some(web), Some(tag)) = (&web, tags.first()) {
+        file.links.push((UNRELEASED.to_lowercase(), format!("{web}/compare/{tag}...HEAD")));
+    }
+    for (i, tag) in tags.iter().enumerate() {
+        let prev = tags.get(i + 1);
+        let range = match prev {
+            Some(prev) => format!("{prev}..{tag}"),
+            None => tag.to_string(),
+        };
+        let (body, newest) = section_body(&git, &config, &range, filter, &constants::CWD)?;
+        // tags without changes of their own, like a pre-release that was
+        // released as is
+        if body.is_empty() {
+            continue;
+        }
+        let version = tag.trim_start_matches('v');
+        let date = newest.map(date);
+        file.sections.push(Section::new(version, date.as_deref(), &body));
+        if let Some(web) = &web {
+            file.links.push((version.to_string(), compare_link(web, prev, tag)));
+        }
+    }
+    // keep links that are not about versions
+    for (name, link) in old.links {
+        let is_version = name.trim_start_matches('v').starts_with(|c: char| c.is_ascii_digit());
+        if !is_version && !name.eq_ignore_ascii_case(UNRELEASED) {
+            file.links.push((name, link));
+        }
+    }
+    std::fs::write(&path, file.to_string())?;
+    msg::write_changelog(&path);
+    Ok(())
+}
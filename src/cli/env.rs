@@ -1,10 +1,7 @@
 use gtea_lib::prelude::*;
 
 pub fn env() -> Result<()> {
-    let script = format!(
-        "\
-",
-    );
+    let script = String::new();
     println!("{script}");
     Ok(())
 }
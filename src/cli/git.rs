@@ -1,30 +1,107 @@
 use gtea_lib::{prelude::*, exec};
+use gtea_lib::commit::scope::infer_scope;
+use gtea_lib::commit::{self, lint, ConventionalCommit, Footer, BREAKING_CHANGE};
+use gtea_lib::config::Config;
+use gtea_lib::exec::{GitBackend, Subprocess};
+use gtea_lib::util::{constants, filepath};
 
-pub fn commit(t: &str, scope: Option<&String>, msg: &str) -> Result<()> {
-    let message: String;
-    if let Some(scope_bind) = scope {
-        message = format!("{t}({scope_bind}): {msg}");
-    } else {
-        message = format!("{t}: {msg}");
+/// optional parts of a commit message given on the command line
+#[derive(Default)]
+pub struct CommitOptions<'a> {
+    pub body: Option<&'a String>,
+    /// description for the `BREAKING CHANGE` footer
+    pub breaking: Option<&'a String>,
+    /// `key=value` pairs
+    pub trailers: Vec<&'a String>,
+    pub issues: Vec<&'a String>,
+}
+
+/// commit staged changes with a conventional commit message.
+///
+/// without *msg* the message is written in `$EDITOR`, without *scope* it is
+/// inferred from the staged files.
+pub fn commit(t: &str, scope: Option<&String>, msg: Option<&String>, options: CommitOptions) -> Result<()> {
+    let config = Config::new()?;
+    let mut commit = ConventionalCommit::new(t, msg.map(|v| v.as_str()).unwrap_or_default());
+    commit.scope = match scope {
+        Some(scope) => Some(scope.to_string()),
+        None => infer_scope(&exec::git_staged_files(&constants::CWD)?, &config.commit)?,
+    };
+    commit.body = options.body.cloned();
+    if let Some(breaking) = options.breaking {
+        commit.breaking = true;
+        commit.footers.push(Footer {
+            token: BREAKING_CHANGE.to_string(),
+            value: breaking.to_string(),
+        });
+    }
+    for trailer in options.trailers {
+        commit.footers.push(parse_trailer(trailer)?);
+    }
+    for issue in options.issues {
+        let value = if issue.chars().all(|c| c.is_ascii_digit()) {
+            format!("#{issue}")
+        } else {
+            issue.to_string()
+        };
+        commit.footers.push(Footer {
+            token: "Refs".to_string(),
+            value,
+        });
     }
+    if msg.is_none() {
+        commit = edit_message(&commit, &config)?;
+    }
+    lint::validate(&commit, &config.commit)?;
+    let message = commit.to_string();
     debug!("message = {message}");
     exec::git_commit(&message)?;
     Ok(())
 }
-pub fn changelog_for_n(n: &str) -> Result<()> {
-    debug!("changelog between 'HEAD..HEAD~{n}'");
-    exec::git_changelog(format!("HEAD~{n}..HEAD").as_str())?;
-    Ok(())
-}
-pub fn changelog_between(start: &str, end: &str) -> Result<()> {
-    debug!("changelog between '{start}..{end}'");
-    exec::git_changelog(format!("{start}..{end}").as_str())?;
-    Ok(())
+/// `key=value` as a commit footer
+fn parse_trailer(trailer: &str) -> Result<Footer> {
+    match trailer.split_once('=') {
+        Some((token, value)) if !token.is_empty() && !token.contains(char::is_whitespace) => Ok(Footer {
+            token: token.to_string(),
+            value: value.to_string(),
+        }),
+        _ => Err(make_err!(Parse, "trailer `{trailer}` is not of the form key=value.")),
+    }
 }
-pub fn changelog_since(hash: &str) -> Result<()> {
-    debug!("changelog between '{hash}..@@{{0}}'");
-    exec::git_changelog(format!("{hash}..@@{{0}}").as_str())?;
-    Ok(())
+
+/// let the user finish *commit* in `$EDITOR`
+///
+/// the message file is kept when the message can't be parsed or fails
+/// validation, so that nothing typed is lost.
+fn edit_message(commit: &ConventionalCommit, config: &Config) -> Result<ConventionalCommit> {
+    let git_dir = Subprocess.git_dir(&constants::CWD)?;
+    let path = filepath::join(&git_dir, "GTEA_EDITMSG");
+    let mut template = format!(
+        "{commit}\n\n# write the description after `{}`.\n# lines starting with '#' are ignored, an empty message aborts the commit.\n#\n",
+        commit.subject().trim_end()
+    );
+    for kind in &config.commit.types {
+        template.push_str(&format!("# {:<10} {}\n", kind.name, kind.description));
+    }
+    std::fs::write(&path, template)?;
+    exec::edit_file(&path)?;
+    let message = commit::strip_comments(&std::fs::read_to_string(&path)?);
+    if message.is_empty() {
+        std::fs::remove_file(&path)?;
+        return Err(make_err!(Missing, "empty commit message, aborting."));
+    }
+    let edited = ConventionalCommit::from_string(&message)
+        .and_then(|commit| lint::validate(&commit, &config.commit).map(|_| commit));
+    match edited {
+        Ok(commit) => {
+            std::fs::remove_file(&path)?;
+            Ok(commit)
+        }
+        Err(err) => {
+            error!("the commit message is kept in {path}.");
+            Err(err)
+        }
+    }
 }
 
 pub fn get_branch_hash(branch: &str) -> Result<()> {
@@ -0,0 +1,91 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use gtea_lib::exec;
+use gtea_lib::prelude::*;
+use gtea_lib::util::{constants, filepath, msg};
+
+const HOOK_NAME: &str = "commit-msg";
+/// marks hooks written by gtea, so foreign hooks are never overwritten
+const HOOK_MARKER: &str = "# managed by gtea";
+/// a foreign hook is moved here on install and run before gtea's checks
+const CHAINED_SUFFIX: &str = ".gtea-orig";
+
+fn hook_script() -> String {
+    format!(
+        r#"#!/bin/sh
+{HOOK_MARKER}, see `gtea hooks`
+chained="$(dirname "$0")/{HOOK_NAME}{CHAINED_SUFFIX}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+exec gtea lint-msg "$1"
+"#
+    )
+}
+
+/// paths of the hook and of the foreign hook it chains to
+fn hook_paths() -> Result<(String, String)> {
+    let dir = exec::git_hooks_dir(&constants::CWD)?;
+    let hook = filepath::join(&dir, HOOK_NAME);
+    let chained = format!("{hook}{CHAINED_SUFFIX}");
+    Ok((hook, chained))
+}
+
+fn is_gtea_hook(path: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|v| v.contains(HOOK_MARKER))
+}
+
+/// write the `commit-msg` hook, keeping an existing hook as chained hook
+pub fn install() -> Result<()> {
+    let (hook, chained) = hook_paths()?;
+    if filepath::exists(&hook) && !is_gtea_hook(&hook) {
+        if filepath::exists(&chained) {
+            return Err(make_err!(
+                Conflict,
+                "both {hook} and {chained} exist; remove one of them first."
+            ));
+        }
+        fs::rename(&hook, &chained)?;
+        msg::hook(HOOK_NAME, "chained", &chained);
+    }
+    fs::create_dir_all(filepath::parent(&hook)?)?;
+    fs::write(&hook, hook_script())?;
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))?;
+    msg::hook(HOOK_NAME, "installed", &hook);
+    Ok(())
+}
+
+/// remove the `commit-msg` hook and put back the hook it chained to
+pub fn uninstall() -> Result<()> {
+    let (hook, chained) = hook_paths()?;
+    if !filepath::exists(&hook) {
+        return Err(make_err!(NotFound, "no {HOOK_NAME} hook installed."));
+    }
+    if !is_gtea_hook(&hook) {
+        return Err(make_err!(Conflict, "{hook} is not managed by gtea."));
+    }
+    fs::remove_file(&hook)?;
+    msg::hook(HOOK_NAME, "removed", &hook);
+    if filepath::exists(&chained) {
+        fs::rename(&chained, &hook)?;
+        msg::hook(HOOK_NAME, "restored", &hook);
+    }
+    Ok(())
+}
+
+pub fn status() -> Result<()> {
+    let (hook, chained) = hook_paths()?;
+    let state = if !filepath::exists(&hook) {
+        "not installed"
+    } else if is_gtea_hook(&hook) {
+        "installed"
+    } else {
+        "not managed by gtea"
+    };
+    msg::hook(HOOK_NAME, state, &hook);
+    if filepath::exists(&chained) {
+        msg::hook(HOOK_NAME, "chained", &chained);
+    }
+    Ok(())
+}
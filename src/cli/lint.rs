@@ -0,0 +1,58 @@
+use gtea_lib::commit::history::{self, LogFilter};
+use gtea_lib::commit::lint;
+use gtea_lib::config::Config;
+use gtea_lib::exec::{GitBackend, Subprocess};
+use gtea_lib::prelude::*;
+use gtea_lib::util::{constants, msg};
+
+use crate::cli::workflow;
+
+/// check the commit message in *path*, as called from the `commit-msg` hook
+pub fn lint_msg(path: &str) -> Result<()> {
+    let config = Config::new()?;
+    let message = std::fs::read_to_string(path)?;
+    let violations = lint::check_message(&message, &config.commit)?;
+    for violation in &violations {
+        msg::violation(violation.rule, &violation.message);
+    }
+    if !violations.is_empty() {
+        return Err(make_err!(Data, "commit message does not follow the commit rules."));
+    }
+    Ok(())
+}
+
+/// check every commit in *range*, defaulting to the commits since the
+/// parent branch
+///
+/// - *format*: `text` or `json`
+pub fn lint(range: Option<&str>, format: &str) -> Result<()> {
+    let git = Subprocess;
+    let config = Config::new()?;
+    let range = match range {
+        Some(range) => range.to_string(),
+        None => {
+            let branch = git.current_branch(&constants::CWD)?;
+            let parent = workflow::get_parent_branch(&git, &config, &branch)?;
+            format!("{parent}..HEAD")
+        }
+    };
+    let entries = history::get_log(&git, &range, &LogFilter::default(), &constants::CWD)?;
+    let reports = lint::check_log(&entries, &config.commit)?;
+    match format {
+        "json" => println!("{}", lint::to_json(&reports)?),
+        _ => {
+            for report in &reports {
+                msg::lint_commit(&report.hash, &report.subject);
+                for violation in &report.violations {
+                    msg::violation(violation.rule, &violation.message);
+                }
+            }
+        }
+    }
+    if !reports.is_empty() {
+        let count = reports.len();
+        let total = entries.len();
+        return Err(make_err!(Data, "{count} of {total} commits in {range} do not follow the commit rules."));
+    }
+    Ok(())
+}
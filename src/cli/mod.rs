@@ -3,3 +3,11 @@ pub mod config;
 pub mod repo;
 pub mod workflow;
 pub mod git;
+pub mod backup;
+pub mod oplog;
+pub mod hooks;
+pub mod lint;
+pub mod changelog;
+pub mod version;
+pub mod release;
+pub mod backport;
@@ -0,0 +1,108 @@
+use gtea_lib::backup;
+use gtea_lib::exec;
+use gtea_lib::exec::{GitBackend, Subprocess};
+use gtea_lib::journal::Journal;
+use gtea_lib::oplog::{self, RefChange, StashEntry, StashState};
+use gtea_lib::prelude::*;
+use gtea_lib::util::{constants, filepath, msg, time};
+
+use crate::cli::workflow;
+
+/// show the operation log, newest first
+pub fn log() -> Result<()> {
+    let git_dir = Subprocess.git_dir(&constants::CWD)?;
+    let operations = oplog::load(&git_dir)?;
+    for operation in operations.iter().rev() {
+        let note = match operation.reverts {
+            Some(id) => format!("(undo of #{id})"),
+            None if operations.iter().any(|v| v.reverts == Some(operation.id)) => "(undone)".to_string(),
+            None => String::new(),
+        };
+        msg::operation(operation.id, &time::format_utc(operation.time), &operation.command, &note);
+        for change in &operation.refs {
+            msg::ref_change(&change.branch, change.before.as_deref(), change.after.as_deref());
+        }
+    }
+    Ok(())
+}
+/// revert the most recent operation that has not been undone yet.
+///
+/// refuses when any branch it touched has moved since.
+pub fn undo() -> Result<()> {
+    let git = Subprocess;
+    let git_dir = git.git_dir(&constants::CWD)?;
+    if Journal::load(&git_dir)?.is_some() {
+        return Err(make_err!(Conflict, "an operation is in progress; run `gtea wf abort` instead."));
+    }
+    let operations = oplog::load(&git_dir)?;
+    let operation = oplog::last_undoable(&operations).ok_or(make_err!(NotFound, "nothing to undo."))?;
+    let command = &operation.command;
+    for change in &operation.refs {
+        let branch = &change.branch;
+        let current = git.rev_parse(&format!("refs/heads/{branch}"), &constants::CWD).ok();
+        if current != change.after {
+            return Err(make_err!(
+                Conflict,
+                "{branch} moved since `{command}`; use `gtea backup restore` instead."
+            ));
+        }
+    }
+    // stashes that were never restored, before any ref moves. popped and
+    // applied stashes are already in the worktree. worktrees that were
+    // removed get theirs once they are added back.
+    let (stashes, removed): (Vec<&StashEntry>, Vec<&StashEntry>) = operation
+        .stashes
+        .iter()
+        .filter(|v| v.state == StashState::Stashed)
+        .partition(|v| filepath::exists(&v.dir));
+    apply_stashes(&git, &stashes)?;
+    let mut refs = vec![];
+    for change in operation.refs.iter().rev() {
+        undo_change(&git, change)?;
+        refs.push(RefChange {
+            branch: change.branch.clone(),
+            dir: change.dir.clone(),
+            before: change.after.clone(),
+            after: change.before.clone(),
+        });
+    }
+    apply_stashes(&git, &removed)?;
+    oplog::record(&git_dir, "undo", Some(operation.id), refs, vec![])?;
+    msg::undo(command);
+    Ok(())
+}
+/// apply the *stashes* that are still in the stash list
+fn apply_stashes(git: &dyn GitBackend, stashes: &[&StashEntry]) -> Result<()> {
+    for stash in stashes {
+        if git.stash_list(&stash.dir)?.contains(&stash.hash) {
+            git.stash_apply(&stash.hash, &stash.dir)?;
+        }
+    }
+    Ok(())
+}
+/// move a branch back to its position before *change*, restoring or
+/// removing its worktree
+fn undo_change(git: &dyn GitBackend, change: &RefChange) -> Result<()> {
+    let branch = &change.branch;
+    let head = format!("refs/heads/{branch}");
+    let dir = workflow::get_branch_dir(git, branch).ok();
+    if change.after.is_some() {
+        backup::save(git, branch, &head, &constants::CWD)?;
+    }
+    match (&change.before, dir) {
+        (Some(before), Some(dir)) => git.reset_keep(before, &dir),
+        (Some(before), None) => {
+            git.update_ref(&head, before, &constants::CWD)?;
+            match &change.dir {
+                Some(dir) if !filepath::exists(dir) => exec::git_worktree_add_branch(dir, branch, &constants::CWD),
+                _ => Ok(()),
+            }
+        }
+        (None, Some(dir)) => {
+            exec::git_worktree_remove(&dir, false, &constants::CWD)?;
+            exec::git_branch_delete(branch, &constants::CWD)?;
+            exec::git_worktree_prune(&constants::CWD)
+        }
+        (None, None) => git.delete_ref(&head, &constants::CWD),
+    }
+}
@@ -0,0 +1,286 @@
+use gtea_lib::changelog::file::FILE_NAME;
+use gtea_lib::changelog::{self, Changelog, Filter, Format};
+use gtea_lib::config::Config;
+use gtea_lib::exec::{self, GitBackend, Subprocess};
+use gtea_lib::prelude::*;
+use gtea_lib::release::{self, package, Channel, Package, Plan};
+use gtea_lib::util::{constants, msg};
+use gtea_lib::version::{self, Bump, NextVersion, Version};
+
+use crate::cli;
+use crate::cli::workflow;
+
+/// version of the next *channel* release after *next*, and the range of
+/// commits since the previous release of that channel
+///
+/// - *versions*: released versions, highest first
+fn channel_version(channel: Channel, next: &NextVersion, versions: &[(String, Version)]) -> (Version, String) {
+    let Some(name) = channel.name() else {
+        return (next.next.clone(), next.range());
+    };
+    let version = version::prerelease(&next.next, name, versions);
+    // notes since the previous pre-release of the same version
+    let previous = versions
+        .iter()
+        .find(|(_, v)| v.is_prerelease() && Version::new(v.major, v.minor, v.patch) == next.next);
+    let range = match previous {
+        Some((tag, _)) => format!("{tag}..HEAD"),
+        None => next.range(),
+    };
+    (version, range)
+}
+
+fn check_tag(tag: &str, root: &str) -> Result<()> {
+    if exec::git_ref_exists(&format!("refs/tags/{tag}"), root)? {
+        return Err(make_err!(Conflict, "tag {tag} already exists."));
+    }
+    Ok(())
+}
+
+/// plan the next *channel* release of the worktree at *root*
+///
+/// stable releases add their CHANGELOG.md section, pre-releases update the
+/// unreleased section.
+fn plan(git: &dyn GitBackend, config: &Config, channel: Channel, root: &str) -> Result<Plan> {
+    if config.release.independent {
+        return plan_packages(git, config, channel, root);
+    }
+    let next = version::next_version(git, &config.version, root)?;
+    if next.bump == Bump::None {
+        let since = match &next.current {
+            Some((tag, _)) => tag.clone(),
+            None => "the first commit".to_string(),
+        };
+        return Err(make_err!(Data, "no commits since {since} call for a release."));
+    }
+    let (version, notes_range) = channel_version(channel, &next, &version::get_versions(git, root)?);
+    let tag = version.tag();
+    check_tag(&tag, root)?;
+    let section = match channel {
+        Channel::Stable => Some(version.to_string()),
+        _ => None,
+    };
+
+    let filter = Filter::default();
+    let mut changes = release::version_changes(root, &config.release, &version.to_string())?;
+    let tags = changelog::stable_tags(git, root)?;
+    let range = next.range();
+    changes.push(cli::changelog::file_change(git, config, Some(&range), section.as_deref(), &filter, &tags, root)?);
+    let notes = changelog::get_changelog(git, &notes_range, &filter, &config.commit, root)?;
+    if notes.is_empty() {
+        return Err(make_err!(Data, "no commits to release in {notes_range}."));
+    }
+    let notes = changelog::render::render(&notes, Format::Plain)?;
+    Ok(Plan::new(&[(tag, notes)], changes, root))
+}
+
+/// plan the next *channel* release of every package with changes, each with
+/// its own version, CHANGELOG.md and `<package>@vX.Y.Z` tag
+fn plan_packages(git: &dyn GitBackend, config: &Config, channel: Channel, root: &str) -> Result<Plan> {
+    let packages = package::discover(root, &config.release)?;
+    let get_changelog = |package: &Package, range: &str| -> Result<Changelog> {
+        let entries = package::get_log(git, package, &packages, range, root)?;
+        Ok(Changelog::new(&entries, &config.commit))
+    };
+
+    let mut releases = vec![];
+    let mut versions = vec![];
+    let mut changelogs = vec![];
+    for package in &packages {
+        let next = package::next_version(git, package, &packages, &config.version, root)?;
+        if next.bump == Bump::None {
+            continue;
+        }
+        let released = version::get_package_versions(git, &package.name, root)?;
+        let (version, notes_range) = channel_version(channel, &next, &released);
+        let notes = get_changelog(package, &notes_range)?;
+        if notes.is_empty() {
+            continue;
+        }
+        let tag = package.tag(&version);
+        check_tag(&tag, root)?;
+
+        let section_changelog = get_changelog(package, &next.range())?;
+        let body = changelog::render::render(&section_changelog, Format::Markdown)?;
+        let newest = cli::changelog::newest(&section_changelog);
+        let tags: Vec<String> = released
+            .into_iter()
+            .filter(|(_, v)| !v.is_prerelease())
+            .map(|(tag, _)| tag)
+            .collect();
+        let section = version.to_string();
+        let section = match channel {
+            Channel::Stable => Some((section.as_str(), tag.as_str())),
+            _ => None,
+        };
+        let path = package.file(FILE_NAME);
+        changelogs.push(cli::changelog::add_section(git, &path, &body, section, newest, &tags, root)?);
+        releases.push((tag, changelog::render::render(&notes, Format::Plain)?));
+        versions.push((package.clone(), version.to_string()));
+    }
+    if releases.is_empty() {
+        return Err(make_err!(Data, "no package has commits that call for a release."));
+    }
+    let mut changes = release::package_changes(root, &packages, &versions)?;
+    changes.extend(changelogs);
+    Ok(Plan::new(&releases, changes, root))
+}
+
+fn check_clean(root: &str) -> Result<()> {
+    if exec::git_local_changes(root)? || !exec::git_staged_files(root)?.is_empty() {
+        return Err(make_err!(Conflict, "worktree at {root} has local changes; commit or stash them first."));
+    }
+    Ok(())
+}
+
+fn show(plan: &Plan) {
+    for change in &plan.changes {
+        msg::diff(&change.diff());
+    }
+    for step in &plan.steps {
+        msg::dry_run(&step.to_string());
+    }
+}
+
+/// cut the next release: bump the version fields, update CHANGELOG.md, then
+/// commit and tag it
+///
+/// - *channel*: nightly releases are pre-releases of the next stable version
+///   and are cut from the nightly branch
+/// - *dry_run*: print the file diffs and git commands without running them
+pub fn release(channel: Channel, dry_run: bool) -> Result<()> {
+    let git = Subprocess;
+    let config = Config::new()?;
+    let root = &exec::git_toplevel(&constants::CWD)?;
+    if channel == Channel::Nightly {
+        let branch = git.current_branch(root)?;
+        let nightly = &config.nightly.branch;
+        if branch != *nightly {
+            return Err(make_err!(Conflict, "nightly releases are cut from {nightly}, not {branch}."));
+        }
+    }
+    check_clean(root)?;
+    let plan = plan(&git, &config, channel, root)?;
+    if dry_run {
+        show(&plan);
+        return Ok(());
+    }
+    plan.run(root)?;
+    for tag in &plan.tags {
+        msg::release(tag);
+    }
+    Ok(())
+}
+
+/// fast-forward main to nightly, cut the stable release of the same commits
+/// in the worktree of main, then fast-forward nightly to the release commit
+///
+/// - *dry_run*: print the file diffs and git commands without running them
+pub fn promote(dry_run: bool) -> Result<()> {
+    let git = Subprocess;
+    let config = Config::new()?;
+    let main_dir = workflow::get_branch_dir(&git, &config.main.branch)?;
+    let nightly_dir = workflow::get_branch_dir(&git, &config.nightly.branch).ok();
+    promote_branch(&git, &config, &main_dir, nightly_dir.as_deref(), dry_run)
+}
+/// - *main_dir*: fullpath to the worktree of main
+/// - *nightly_dir*: fullpath to the worktree of nightly, if it is checked out
+fn promote_branch(
+    git: &dyn GitBackend,
+    config: &Config,
+    main_dir: &str,
+    nightly_dir: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    let main = &config.main.branch;
+    let nightly = &config.nightly.branch;
+    let (ahead, behind) = git.ahead_behind(main, nightly, main_dir)?;
+    if behind > 0 {
+        return Err(make_err!(Conflict, "{main} has {behind} commits that are not in {nightly}; can't fast-forward."));
+    }
+    check_clean(main_dir)?;
+    if let Some(dir) = nightly_dir {
+        check_clean(dir)?;
+    }
+
+    if dry_run {
+        // after the fast-forward main has the tree of nightly
+        let dir = match (ahead, nightly_dir) {
+            (0, _) => main_dir,
+            (_, Some(dir)) => dir,
+            (_, None) => return Err(make_err!(NotFound, "worktree for branch {nightly} not found.")),
+        };
+        if ahead > 0 {
+            msg::dry_run(&format!("git merge --ff-only {nightly}"));
+        }
+        show(&plan(git, config, Channel::Stable, dir)?);
+        msg::dry_run(&match nightly_dir {
+            Some(_) => format!("git merge --ff-only {main}"),
+            None => format!("git update-ref refs/heads/{nightly} {main}"),
+        });
+        return Ok(());
+    }
+    if ahead > 0 {
+        exec::git_merge_ff(nightly, main_dir)?;
+    }
+    let plan = plan(git, config, Channel::Stable, main_dir)?;
+    plan.run(main_dir)?;
+    for tag in &plan.tags {
+        msg::release(tag);
+    }
+    // the release commit goes back to nightly, so the next promote can
+    // fast-forward again
+    match nightly_dir {
+        Some(dir) => exec::git_merge_ff(main, dir),
+        None => {
+            let head = git.rev_parse("HEAD", main_dir)?;
+            git.update_ref(&format!("refs/heads/{nightly}"), &head, main_dir)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gtea_lib::config::Config;
+    use gtea_lib::exec::{GitBackend, GitCommand, Subprocess};
+
+    fn git(args: &[&str], pwd: &str) -> String {
+        GitCommand::new().args(args.iter().copied()).cwd(pwd).output().unwrap()
+    }
+
+    /// commit *file* on the branch checked out at *dir*
+    fn commit(dir: &str, file: &str, message: &str) {
+        std::fs::write(format!("{dir}/{file}"), message).unwrap();
+        git(&["add", file], dir);
+        git(&["commit", "-q", "-m", message], dir);
+    }
+
+    #[test]
+    fn promote_twice() {
+        let root = std::env::temp_dir().join(format!("gtea-promote-{}", std::process::id()));
+        let root = root.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(root);
+        let main = format!("{root}/main");
+        let nightly = format!("{root}/nightly");
+        std::fs::create_dir_all(&main).unwrap();
+        git(&["init", "-q", "-b", "main"], &main);
+        git(&["config", "user.name", "gtea"], &main);
+        git(&["config", "user.email", "gtea@example.com"], &main);
+        std::fs::write(format!("{main}/Cargo.toml"), "[package]\nname = \"app\"\nversion = \"0.1.0\"\n").unwrap();
+        git(&["add", "Cargo.toml"], &main);
+        git(&["commit", "-q", "-m", "chore: init"], &main);
+        git(&["worktree", "add", "-q", "-b", "nightly", &nightly], &main);
+
+        let mut config = Config::default();
+        config.nightly.enable = true;
+        commit(&nightly, "a.txt", "feat: add a");
+        super::promote_branch(&Subprocess, &config, &main, Some(&nightly), false).unwrap();
+        commit(&nightly, "b.txt", "feat: add b");
+        super::promote_branch(&Subprocess, &config, &main, Some(&nightly), false).unwrap();
+
+        let tags = Subprocess.tags(&main).unwrap();
+        assert_eq!(tags, vec!["v0.2.0", "v0.1.0"]);
+        assert_eq!(Subprocess.rev_parse("nightly", &main).unwrap(), Subprocess.rev_parse("main", &main).unwrap());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
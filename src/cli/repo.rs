@@ -8,7 +8,7 @@ pub fn init() -> Result<()> {
     Ok(())
 }
 pub fn clone(url: &str) -> Result<()> {
-    exec::git_clone(&format!("{url} -- --filter=blob:none"))?;
+    exec::git_clone(url, &["--filter=blob:none"])?;
     Ok(())
 }
 pub fn create(name: Option<&str>) -> Result<()> {
@@ -23,7 +23,7 @@ pub fn push(branch: Option<&str>) -> Result<()> {
     if let Some(branch) = branch {
         exec::git_push("origin", branch)?;
     } else {
-        let branch = exec::git_current_branch(&*constants::CWD)?;
+        let branch = exec::git_current_branch(&constants::CWD)?;
         exec::git_push("origin", &branch)?;
     }
     Ok(())
@@ -0,0 +1,41 @@
+use gtea_lib::config::Config;
+use gtea_lib::exec::Subprocess;
+use gtea_lib::prelude::*;
+use gtea_lib::release::package;
+use gtea_lib::util::{constants, msg};
+use gtea_lib::version::{self, NextVersion};
+
+fn explain_bump(next: &NextVersion) {
+    match &next.current {
+        Some((tag, _)) => msg::version_current(tag, &next.bump.to_string()),
+        None => msg::version_current("(no release)", &next.bump.to_string()),
+    }
+    for (bump, entry) in &next.commits {
+        msg::version_commit(&bump.to_string(), entry.short_hash(), entry.subject());
+    }
+}
+
+/// print the next version from the commits since the latest release, or
+/// the next version of each package when they are versioned independently
+///
+/// - *explain*: also list the commits that decided the bump
+pub fn next(explain: bool) -> Result<()> {
+    let config = Config::new()?;
+    if config.release.independent {
+        let packages = package::discover(&constants::CWD, &config.release)?;
+        for package in &packages {
+            let next = package::next_version(&Subprocess, package, &packages, &config.version, &constants::CWD)?;
+            if explain {
+                explain_bump(&next);
+            }
+            println!("{} {}", package.name, next.next);
+        }
+        return Ok(());
+    }
+    let next = version::next_version(&Subprocess, &config.version, &constants::CWD)?;
+    if explain {
+        explain_bump(&next);
+    }
+    println!("{}", next.next);
+    Ok(())
+}
@@ -1,8 +1,16 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::Path;
+
+use gtea_lib::backup;
 use gtea_lib::config::Config;
 use gtea_lib::exec;
+use gtea_lib::exec::{GitBackend, Subprocess};
+use gtea_lib::journal::{Journal, JournalStep, StepState};
+use gtea_lib::oplog::{self, RefChange, StashEntry, StashState};
 use gtea_lib::prelude::*;
 use gtea_lib::util::constants;
 use gtea_lib::util::filepath;
+use gtea_lib::util::msg;
 use gtea_lib::worktree;
 
 pub fn is_bare_root(dir: &str) -> bool {
@@ -10,64 +18,306 @@ pub fn is_bare_root(dir: &str) -> bool {
     checks.push(filepath::exists(&format!("{}/worktrees", dir)));
     checks.push(filepath::exists(&format!("{}/branches", dir)));
     checks.push(filepath::exists(&format!("{}/HEAD", dir)));
-    let result = checks
-        .iter()
-        .map(|v| {
-            if !v {
-                return Err(());
-            }
-            Ok(())
-        })
-        .collect::<std::result::Result<(), ()>>();
-    match result {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+    checks.iter().all(|v| *v)
 }
 pub fn is_worktree() -> bool {
-    if !is_bare_root(&filepath::join(&*constants::CWD, "..")) {
+    if !is_bare_root(&filepath::join(&constants::CWD, "..")) {
         return false;
     }
     if !filepath::exists(&format!("{}/.git", &*constants::CWD)) {
         return false;
     }
-    return true;
+    true
+}
+/// get fullpath to the bare repository root
+///
+/// worktrees are checked out as direct children of the bare root, so this
+/// checks the current directory and its parent.
+pub fn get_bare_root() -> Result<String> {
+    let cwd = constants::CWD.to_string();
+    if is_bare_root(&cwd) {
+        return Ok(cwd);
+    }
+    let parent = filepath::parent(&cwd)?;
+    if is_bare_root(&parent) {
+        return Ok(parent);
+    }
+    Err(make_err!(NotFound, "bare repository root not found from {cwd}."))
 }
 
 /// rebase root onto ahead
 ///
 /// - *root*: root branch as fullpath
 /// - *ahead*: branch with updated changes as fullpath
-pub fn rebase(root: &str, ahead: &str) -> Result<()> {
-    // stash local changes
-    let local_changes = exec::git_local_changes(root)?;
-    if local_changes {
-        exec::git_stash_push(root)?;
-    }
-    // $ git rebase ahead
-    let branch = exec::git_current_branch(ahead)?;
-    debug!("changes from {branch} on {root}");
-    exec::git_rebase(&branch, root)?;
-    // apply stashed changes
-    if local_changes {
-        exec::git_stash_pop(root)?;
+pub fn rebase(git: &dyn GitBackend, root: &str, ahead: &str) -> Result<()> {
+    let branch = git.current_branch(root)?;
+    let onto = git.current_branch(ahead)?;
+    let mut journal = Journal::new("rebase");
+    journal.step(&branch, &onto, root);
+    run_operation(git, journal)
+}
+/// start *journal* as the operation in progress and run its steps.
+///
+/// only one operation can be in progress at a time.
+pub fn run_operation(git: &dyn GitBackend, mut journal: Journal) -> Result<()> {
+    let git_dir = git.git_dir(&constants::CWD)?;
+    if let Some(current) = Journal::load(&git_dir)? {
+        let operation = current.operation;
+        return Err(make_err!(
+            Conflict,
+            "{operation} is in progress; run `gtea wf continue` or `gtea wf abort` first."
+        ));
     }
-    Ok(())
+    journal.save(&git_dir)?;
+    run_journal(git, &git_dir, &mut journal)
+}
+/// run the remaining steps of *journal*, saving progress after each step
+///
+/// each worktree has its local changes stashed before the rebase and
+/// restored after it. when a rebase stops on a conflict the journal is kept
+/// so that the operation can be continued or aborted.
+fn run_journal(git: &dyn GitBackend, git_dir: &str, journal: &mut Journal) -> Result<()> {
+    for i in 0..journal.steps.len() {
+        let step = journal.steps[i].clone();
+        match step.state {
+            StepState::Done => continue,
+            StepState::Pending => {
+                msg::rebase(&step.branch, &step.onto);
+                let head = backup::save(git, &step.branch, "HEAD", &step.dir)?.hash;
+                journal.steps[i].head = Some(head);
+                // stash local changes
+                if git.local_changes(&step.dir)? {
+                    git.stash_push(&step.dir)?;
+                    journal.steps[i].stash = Some(git.rev_parse("refs/stash", &step.dir)?);
+                }
+                // a parent rebased by an earlier step only brings its own
+                // commits along
+                let upstream = journal.steps[..i].iter().find(|v| v.branch == step.onto).and_then(|v| v.head.clone());
+                journal.steps[i].upstream = upstream.clone();
+                journal.steps[i].state = StepState::Rebasing;
+                journal.save(git_dir)?;
+                // $ git rebase onto
+                // $ git rebase --onto onto upstream
+                debug!("changes from {} on {}", step.onto, step.dir);
+                let result = match &upstream {
+                    Some(upstream) => git.rebase_onto(&step.onto, upstream, &step.dir),
+                    None => git.rebase(&step.onto, &step.dir),
+                };
+                if let Err(err) = result {
+                    if git.rebase_in_progress(&step.dir)? {
+                        return Err(rebase_stopped(&step, err));
+                    }
+                    // the rebase did not start, so the step can run again
+                    restore_stash(git, &journal.steps[i])?;
+                    journal.steps[i] = JournalStep {
+                        state: StepState::Pending,
+                        head: None,
+                        upstream: None,
+                        stash: None,
+                        restored: None,
+                        ..step
+                    };
+                    journal.save(git_dir)?;
+                    return Err(err);
+                }
+            }
+            StepState::Rebasing => {
+                if git.rebase_in_progress(&step.dir)? {
+                    if let Err(err) = git.rebase_continue(&step.dir) {
+                        if git.rebase_in_progress(&step.dir)? {
+                            return Err(rebase_stopped(&step, err));
+                        }
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        // apply stashed changes
+        journal.steps[i].restored = restore_stash(git, &journal.steps[i])?;
+        journal.steps[i].state = StepState::Done;
+        journal.save(git_dir)?;
+    }
+    if let Some(branch) = &journal.push {
+        git.push("origin", branch, &constants::CWD)?;
+    }
+    let mut refs = vec![];
+    let mut stashes = vec![];
+    for step in &journal.steps {
+        refs.push(RefChange {
+            branch: step.branch.clone(),
+            dir: Some(step.dir.clone()),
+            before: step.head.clone(),
+            after: Some(git.rev_parse("HEAD", &step.dir)?),
+        });
+        if let Some(hash) = &step.stash {
+            stashes.push(StashEntry {
+                dir: step.dir.clone(),
+                hash: hash.clone(),
+                state: step.restored.unwrap_or(StashState::Stashed),
+            });
+        }
+    }
+    oplog::record(git_dir, &format!("wf {}", journal.operation), None, refs, stashes)?;
+    Journal::remove(git_dir)
+}
+fn rebase_stopped(step: &JournalStep, err: Error) -> Error {
+    let JournalStep { branch, onto, dir, .. } = step;
+    make_err!(
+        Conflict,
+        "rebase of {branch} onto {onto} stopped in {dir}; resolve it and run `gtea wf continue`, or `gtea wf abort` to roll back.\n{err}"
+    )
+}
+/// pop the stash *step* created, or apply it when other entries were
+/// stashed on top of it
+///
+/// returns how the stash was restored, `None` when there is nothing to
+/// restore.
+fn restore_stash(git: &dyn GitBackend, step: &JournalStep) -> Result<Option<StashState>> {
+    let (Some(stash), None) = (&step.stash, step.restored) else {
+        return Ok(step.restored);
+    };
+    if git.stash_list(&step.dir)?.first() == Some(stash) {
+        git.stash_pop(&step.dir)?;
+        return Ok(Some(StashState::Popped));
+    }
+    git.stash_apply(stash, &step.dir)?;
+    warn!("applied stash {stash} in {}, it is left in the stash list.", step.dir);
+    Ok(Some(StashState::Applied))
+}
+/// continue the operation in progress after resolving a conflict
+pub fn resume() -> Result<()> {
+    resume_operation(&Subprocess)
+}
+pub fn resume_operation(git: &dyn GitBackend) -> Result<()> {
+    let git_dir = git.git_dir(&constants::CWD)?;
+    let mut journal = Journal::load(&git_dir)?.ok_or(make_err!(NotFound, "no operation in progress."))?;
+    run_journal(git, &git_dir, &mut journal)
+}
+/// roll back every worktree touched by the operation in progress to its
+/// HEAD before the operation and restore its stashed changes
+pub fn abort() -> Result<()> {
+    abort_operation(&Subprocess)
+}
+pub fn abort_operation(git: &dyn GitBackend) -> Result<()> {
+    let git_dir = git.git_dir(&constants::CWD)?;
+    let journal = Journal::load(&git_dir)?.ok_or(make_err!(NotFound, "no operation in progress."))?;
+    for step in journal.steps.iter().rev() {
+        if step.state == StepState::Pending {
+            continue;
+        }
+        msg::rollback(&step.branch);
+        if step.state == StepState::Rebasing && git.rebase_in_progress(&step.dir)? {
+            git.rebase_abort(&step.dir)?;
+        }
+        // finished steps have their stash back in the worktree, which is
+        // stashed again around the reset
+        let restash = step.state == StepState::Done && step.restored.is_some() && git.local_changes(&step.dir)?;
+        if restash {
+            git.stash_push(&step.dir)?;
+        }
+        if let Some(head) = &step.head {
+            git.reset_keep(head, &step.dir)?;
+        }
+        if restash {
+            git.stash_pop(&step.dir)?;
+        }
+        if step.state == StepState::Rebasing {
+            restore_stash(git, step)?;
+        }
+    }
+    Journal::remove(&git_dir)
 }
 /// get parent branch name
 ///
+/// the parent is taken from the first of
+/// - `[branches."<child>"]` in gtea.toml
+/// - the local upstream branch in git config, as set by `wf create`
+/// - nightly if enabled, otherwise main
+///
 /// - *child*: child branch name
-pub fn get_parent_branch(child: &str) -> Result<String> {
-    let config = Config::new()?;
+pub fn get_parent_branch(git: &dyn GitBackend, config: &Config, child: &str) -> Result<String> {
+    match get_declared_parent(git, config, child)? {
+        Some(parent) => Ok(parent),
+        None => Ok(get_default_parent(config, child)),
+    }
+}
+/// get the parent branch set in gtea.toml or tracked as local upstream
+///
+/// - *child*: child branch name
+pub fn get_declared_parent(git: &dyn GitBackend, config: &Config, child: &str) -> Result<Option<String>> {
+    if let Some(branch) = config.branches.get(child) {
+        return Ok(Some(branch.parent.clone()));
+    }
+    get_tracked_parent(git, child)
+}
+/// nightly if enabled, otherwise main
+fn get_default_parent(config: &Config, child: &str) -> String {
     let parent = if config.nightly.enable && child != config.nightly.branch {
-        config.nightly.branch
+        &config.nightly.branch
     } else {
-        config.main.branch
+        &config.main.branch
     };
+    parent.to_string()
+}
+/// get the local branch *child* tracks as upstream
+///
+/// - *child*: child branch name
+pub fn get_tracked_parent(git: &dyn GitBackend, child: &str) -> Result<Option<String>> {
+    let remote = git.config_get(&format!("branch.{child}.remote"), &constants::CWD)?;
+    if remote.as_deref() != Some(".") {
+        return Ok(None);
+    }
+    let merge = git.config_get(&format!("branch.{child}.merge"), &constants::CWD)?;
+    let parent = merge
+        .and_then(|v| v.strip_prefix("refs/heads/").map(|v| v.to_string()))
+        .filter(|v| v != child);
     Ok(parent)
 }
-pub fn get_branch_dir(branch: &str) -> Result<String> {
-    let worktrees = worktree::get_worktree_list(&*constants::CWD)?;
+/// get map of parent branch to its child branches for all local branches
+///
+/// branches without a declared parent are only included when they are
+/// feature branches or the nightly branch, under their default parent.
+pub fn get_branch_tree(git: &dyn GitBackend, config: &Config) -> Result<BTreeMap<String, Vec<String>>> {
+    let prefix = format!("{}/", config.feature.prefix);
+    let nightly = config.nightly.enable.then_some(&config.nightly.branch);
+    let mut tree: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for branch in git.branches(&constants::CWD)? {
+        if branch == config.main.branch {
+            continue;
+        }
+        let parent = match get_declared_parent(git, config, &branch)? {
+            Some(parent) => parent,
+            None if branch.starts_with(&prefix) || nightly == Some(&branch) => get_default_parent(config, &branch),
+            None => continue,
+        };
+        if parent == branch {
+            continue;
+        }
+        tree.entry(parent).or_default().push(branch);
+    }
+    Ok(tree)
+}
+/// get (parent, child) pairs for every branch stacked on *root*, with each
+/// parent listed before its children
+///
+/// - *tree*: branch tree from `get_branch_tree`
+/// - *root*: branch name to start from
+pub fn get_descendants(tree: &BTreeMap<String, Vec<String>>, root: &str) -> Vec<(String, String)> {
+    let mut descendants = vec![];
+    let mut seen = BTreeSet::from([root.to_string()]);
+    let mut queue = VecDeque::from([root.to_string()]);
+    while let Some(parent) = queue.pop_front() {
+        for child in tree.get(&parent).into_iter().flatten() {
+            if seen.insert(child.clone()) {
+                descendants.push((parent.clone(), child.clone()));
+                queue.push_back(child.clone());
+            }
+        }
+    }
+    descendants
+}
+pub fn get_branch_dir(git: &dyn GitBackend, branch: &str) -> Result<String> {
+    let worktrees = worktree::get_worktree_list(git, &constants::CWD)?;
     debug!("{:?}", worktrees);
     let worktrees: Vec<Option<String>> = worktrees.iter().map(|v| {
         if let Some(branch_bind) = &v.branch {
@@ -80,7 +330,7 @@ pub fn get_branch_dir(branch: &str) -> Result<String> {
     }).collect();
     let worktrees: Vec<String> = worktrees.into_iter().flatten().collect();
     debug!("found worktrees. {:?}", worktrees);
-    match worktrees.get(0) {
+    match worktrees.first() {
         Some(v) => Ok(v.to_owned()),
         None => Err(make_err!(NotFound, "worktree for branch {branch} not found.")) 
     }
@@ -88,41 +338,475 @@ pub fn get_branch_dir(branch: &str) -> Result<String> {
 /// get fullpath to parent branch
 ///
 /// - *child*: child branch name
-pub fn get_parent_dir(child: &str) -> Result<String> {
-    let parent = get_parent_branch(&child)?;
-    get_branch_dir(&parent)
+pub fn get_parent_dir(git: &dyn GitBackend, config: &Config, child: &str) -> Result<String> {
+    let parent = get_parent_branch(git, config, child)?;
+    get_branch_dir(git, &parent)
 }
 /// update child branch to changes from parent branch
 ///
 /// get parent branch
 /// $ git rebase parent
-///
-/// rebase(cwd, parent)
 pub fn update() -> Result<()> {
-    let branch = exec::git_current_branch(&*constants::CWD)?;
-    let parent_dir = get_parent_dir(&branch)?;
-    rebase(&*constants::CWD, &parent_dir)?;
-    Ok(())
+    let config = Config::new()?;
+    update_branch(&Subprocess, &config, &constants::CWD)
+}
+/// update the branch checked out at *dir* to changes from its parent branch
+pub fn update_branch(git: &dyn GitBackend, config: &Config, dir: &str) -> Result<()> {
+    let branch = git.current_branch(dir)?;
+    let parent = get_parent_branch(git, config, &branch)?;
+    let mut journal = Journal::new("update");
+    journal.step(&branch, &parent, dir);
+    run_operation(git, journal)
 }
 /// push local changes to parent branch.
 ///
 /// work on parent branch
 /// - $ git rebase child
-/// (optional) push parent branch
+/// - (optional) push parent branch
 pub fn push(upstream: bool) -> Result<()> {
-    let branch = exec::git_current_branch(&*constants::CWD)?;
-    let branch_dir = get_branch_dir(&branch)?;
-    let parent = get_parent_branch(&branch)?;
-    let parent_dir = get_parent_dir(&branch)?;
-    rebase(&parent_dir, &branch_dir)?;
+    let config = Config::new()?;
+    push_branch(&Subprocess, &config, &constants::CWD, upstream)
+}
+/// push the branch checked out at *dir* to its parent branch
+pub fn push_branch(git: &dyn GitBackend, config: &Config, dir: &str, upstream: bool) -> Result<()> {
+    let branch = git.current_branch(dir)?;
+    let parent = get_parent_branch(git, config, &branch)?;
+    let parent_dir = get_branch_dir(git, &parent)?;
+    let mut journal = Journal::new("push");
+    journal.step(&parent, &branch, &parent_dir);
     if upstream {
-        exec::git_push("origin", &parent)?;
+        journal.push = Some(parent);
     }
-    Ok(())
+    run_operation(git, journal)
+}
+/// split a feature name into its unprefixed name and prefixed branch name
+///
+/// - *name*: feature name, with or without the configured prefix
+pub fn get_feature_branch(config: &Config, name: &str) -> Result<(String, String)> {
+    let prefix = format!("{}/", config.feature.prefix);
+    let name = name.strip_prefix(&prefix).unwrap_or(name);
+    Ok((name.to_string(), format!("{prefix}{name}")))
 }
+/// create child branch in a new worktree.
+///
+/// get parent branch
+/// $ git worktree add --track -b prefix/name root/name parent
+///
+/// the new branch tracks its parent branch as upstream.
 pub fn create(name: &str) -> Result<()> {
-    todo!()
+    let config = Config::new()?;
+    let (name, branch) = get_feature_branch(&config, name)?;
+    if get_branch_dir(&Subprocess, &branch).is_ok() {
+        return Err(make_err!(Conflict, "worktree for branch {branch} already exists."));
+    }
+    let parent = get_parent_branch(&Subprocess, &config, &branch)?;
+    let root = get_bare_root()?;
+    let dir = filepath::join(&root, &name);
+    if filepath::exists(&dir) {
+        return Err(make_err!(Conflict, "directory {dir} already exists."));
+    }
+    msg::create_worktree(&branch, &dir);
+    exec::git_worktree_add(&dir, &branch, &parent, &root)?;
+    let git = Subprocess;
+    let change = RefChange {
+        branch: branch.clone(),
+        dir: Some(dir),
+        before: None,
+        after: Some(git.rev_parse(&format!("refs/heads/{branch}"), &root)?),
+    };
+    oplog::record(&git.git_dir(&root)?, "wf create", None, vec![change], vec![])?;
+    Ok(())
+}
+/// check that a branch can be removed without losing work
+///
+/// - *branch*: branch name
+/// - *dir*: fullpath to the worktree of *branch*
+pub fn check_removable(git: &dyn GitBackend, config: &Config, branch: &str, dir: &str) -> Result<()> {
+    if !exec::git_status(dir)?.is_empty() {
+        return Err(make_err!(
+            Conflict,
+            "worktree for {branch} has uncommitted or untracked changes; pass --force to stash them."
+        ));
+    }
+    let remote = format!("refs/remotes/origin/{branch}");
+    if exec::git_ref_exists(&remote, dir)? {
+        let unpushed = exec::git_rev_list_count(&format!("{remote}..heads/{branch}"), dir)?;
+        if unpushed > 0 {
+            return Err(make_err!(Conflict, "{branch} has {unpushed} unpushed commits."));
+        }
+    }
+    let parent = get_parent_branch(git, config, branch)?;
+    let unmerged = exec::git_rev_list_count(&format!("heads/{parent}..heads/{branch}"), dir)?;
+    if unmerged > 0 {
+        return Err(make_err!(Conflict, "{branch} has {unmerged} commits not in {parent}."));
+    }
+    Ok(())
+}
+/// remove a child branch and its worktree.
+///
+/// refuses when the worktree has local changes, unpushed commits or commits
+/// that are not in its parent branch, unless *force* is set. the branch is
+/// saved to a backup ref first, and with *force* its local changes are
+/// stashed.
+///
+/// $ git worktree remove dir
+/// $ git branch -D branch
+/// $ git worktree prune
+pub fn remove(name: &str, force: bool) -> Result<()> {
+    let config = Config::new()?;
+    let (_, branch) = get_feature_branch(&config, name)?;
+    let dir = get_branch_dir(&Subprocess, &branch)?;
+    let root = get_bare_root()?;
+    if Path::new(&*constants::CWD).starts_with(Path::new(&dir)) {
+        return Err(make_err!(Conflict, "cannot remove the current worktree {dir}."));
+    }
+    if !force {
+        check_removable(&Subprocess, &config, &branch, &dir)?;
+    }
+    let mut stashes = vec![];
+    if !exec::git_status(&dir)?.is_empty() {
+        exec::git_stash_save(&format!("gtea: wf remove {branch}"), &dir)?;
+        let hash = Subprocess.rev_parse("refs/stash", &dir)?;
+        msg::stash(&branch, &hash);
+        stashes.push(StashEntry {
+            dir: dir.clone(),
+            hash,
+            state: StashState::Stashed,
+        });
+    }
+    let backup = backup::save(&Subprocess, &branch, &format!("refs/heads/{branch}"), &root)?;
+    msg::backup(&branch, &backup.gref());
+    msg::remove(&branch);
+    exec::git_worktree_remove(&dir, force, &root)?;
+    exec::git_branch_delete(&branch, &root)?;
+    exec::git_worktree_prune(&root)?;
+    let change = RefChange {
+        branch,
+        dir: Some(dir),
+        before: Some(backup.hash),
+        after: None,
+    };
+    oplog::record(&Subprocess.git_dir(&root)?, "wf remove", None, vec![change], stashes)?;
+    Ok(())
+}
+
+/// rebase every branch stacked on *branch* onto its updated parent.
+///
+/// defaults to the current branch. stops at the first conflict, see
+/// `resume` and `abort`.
+pub fn restack(branch: Option<&str>) -> Result<()> {
+    let config = Config::new()?;
+    let git = Subprocess;
+    let branch = match branch {
+        Some(v) => v.to_string(),
+        None => git.current_branch(&constants::CWD)?,
+    };
+    restack_branch(&git, &config, &branch)
+}
+/// rebase every worktree stacked on *branch* onto its parent, parents first
+pub fn restack_branch(git: &dyn GitBackend, config: &Config, branch: &str) -> Result<()> {
+    let tree = get_branch_tree(git, config)?;
+    let mut journal = Journal::new("restack");
+    for (parent, child) in get_descendants(&tree, branch) {
+        // only branches stacked on purpose, not ones shown under their
+        // default parent
+        if get_declared_parent(git, config, &child)?.is_none() {
+            continue;
+        }
+        match get_branch_dir(git, &child) {
+            Ok(dir) => journal.step(&child, &parent, &dir),
+            Err(_) => warn!("no worktree for {child}, skipping."),
+        };
+    }
+    run_operation(git, journal)
+}
+/// show the branch hierarchy with commits ahead of and behind each parent
+pub fn tree() -> Result<()> {
+    let config = Config::new()?;
+    let git = Subprocess;
+    let tree = get_branch_tree(&git, &config)?;
+    let branches: BTreeSet<String> = git.branches(&constants::CWD)?.into_iter().collect();
+    let mut seen = BTreeSet::new();
+    // parents without a local branch are shown as roots too
+    let mut roots = vec![config.main.branch.clone()];
+    roots.extend(tree.keys().filter(|v| !branches.contains(*v) && **v != config.main.branch).cloned());
+    for root in roots {
+        msg::tree_node("", &root, None);
+        print_tree(&git, &tree, &branches, &root, "", &mut seen)?;
+    }
+    let cycle: Vec<&String> = tree.values().flatten().filter(|v| !seen.contains(*v)).collect();
+    if !cycle.is_empty() {
+        warn!("branches in a parent cycle: {:?}", cycle);
+    }
+    Ok(())
+}
+fn print_tree(
+    git: &dyn GitBackend,
+    tree: &BTreeMap<String, Vec<String>>,
+    branches: &BTreeSet<String>,
+    parent: &str,
+    prefix: &str,
+    seen: &mut BTreeSet<String>,
+) -> Result<()> {
+    let Some(children) = tree.get(parent) else {
+        return Ok(());
+    };
+    for (i, child) in children.iter().enumerate() {
+        if !seen.insert(child.clone()) {
+            continue;
+        }
+        let last = i == children.len() - 1;
+        let (node, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        // parents without a local branch have nothing to count against
+        let counts = if branches.contains(parent) {
+            Some(git.ahead_behind(parent, child, &constants::CWD)?)
+        } else {
+            None
+        };
+        msg::tree_node(&format!("{prefix}{node}"), child, counts);
+        print_tree(git, tree, branches, child, &format!("{prefix}{indent}"), seen)?;
+    }
+    Ok(())
 }
-pub fn remove(name: &str) -> Result<()> {
-    todo!()
+
+#[cfg(test)]
+mod tests {
+    use gtea_lib::config::{Config, ConfigBranch};
+    use gtea_lib::exec::MockBackend;
+    use gtea_lib::journal::{Journal, StepState};
+    use gtea_lib::oplog::{self, StashState};
+    use gtea_lib::prelude::*;
+    use gtea_lib::util::constants;
+
+    const WORKTREES: &str = "worktree /repo.git\nbare\n\nworktree /repo.git/main\nHEAD 1111111111111111111111111111111111111111\nbranch refs/heads/main\n\nworktree /repo.git/foo\nHEAD 2222222222222222222222222222222222222222\nbranch refs/heads/feature/foo\n\n";
+
+    #[test]
+    fn parent_branch() {
+        let mut config = Config::default();
+        config.branches.insert(
+            "feature/api".to_string(),
+            ConfigBranch { parent: "feature/core".to_string() },
+        );
+        let git = MockBackend::new();
+        assert_eq!(super::get_parent_branch(&git, &config, "feature/api").unwrap(), "feature/core");
+        assert!(git.calls().is_empty());
+
+        git.script("config_get", ".").script("config_get", "refs/heads/feature/core");
+        assert_eq!(super::get_parent_branch(&git, &config, "feature/ui").unwrap(), "feature/core");
+
+        git.script("config_get", "origin");
+        assert_eq!(super::get_parent_branch(&git, &config, "feature/db").unwrap(), "main");
+    }
+
+    #[test]
+    fn branch_tree() {
+        let mut config = Config::default();
+        config.branches.insert(
+            "feature/api".to_string(),
+            ConfigBranch { parent: "feature/core".to_string() },
+        );
+        let git = MockBackend::new();
+        git.script("branches", "feature/api\nfeature/core\nmain\nrelease/1.0");
+        let tree = super::get_branch_tree(&git, &config).unwrap();
+        // release/1.0 has no declared parent and isn't a feature branch
+        assert_eq!(tree["main"], vec!["feature/core"]);
+        assert_eq!(tree["feature/core"], vec!["feature/api"]);
+    }
+
+    #[test]
+    fn descendants() {
+        let mut tree = std::collections::BTreeMap::new();
+        tree.insert("main".to_string(), vec!["a".to_string(), "b".to_string()]);
+        tree.insert("a".to_string(), vec!["c".to_string()]);
+        tree.insert("c".to_string(), vec!["a".to_string()]);
+        let pairs: Vec<(&str, &str)> = vec![("main", "a"), ("main", "b"), ("a", "c")];
+        let expected: Vec<(String, String)> = pairs.iter().map(|(p, c)| (p.to_string(), c.to_string())).collect();
+        assert_eq!(super::get_descendants(&tree, "main"), expected);
+        assert_eq!(super::get_descendants(&tree, "b"), vec![]);
+    }
+
+    /// mock backend with a fresh git dir, scripted for *operations* calls
+    fn mock(name: &str, operations: usize) -> (MockBackend, String) {
+        let dir = std::env::temp_dir().join(format!("gtea-workflow-{name}-{}", std::process::id()));
+        let dir = dir.to_str().unwrap().to_string();
+        let _ = std::fs::remove_dir_all(&dir);
+        let git = MockBackend::new();
+        for _ in 0..operations {
+            git.script("git_dir", &dir);
+        }
+        (git, dir)
+    }
+
+    /// recorded calls to any of *ops*
+    fn calls(git: &MockBackend, ops: &[&str]) -> Vec<String> {
+        git.calls()
+            .into_iter()
+            .filter(|v| ops.iter().any(|op| v.split(' ').next() == Some(*op)))
+            .collect()
+    }
+
+    #[test]
+    fn restack() {
+        let worktrees = format!("{WORKTREES}worktree /repo.git/api\nHEAD 3333333333333333333333333333333333333333\nbranch refs/heads/feature/api\n\n");
+        let mut config = Config::default();
+        config.branches.insert(
+            "feature/api".to_string(),
+            ConfigBranch { parent: "feature/foo".to_string() },
+        );
+        config.branches.insert(
+            "feature/foo".to_string(),
+            ConfigBranch { parent: "main".to_string() },
+        );
+        let (git, dir) = mock("restack", 1);
+        // feature/bar is only shown under main by default, it isn't restacked
+        git.script("branches", "feature/api\nfeature/bar\nfeature/foo\nmain")
+            .script("worktree_list", &worktrees)
+            .script("worktree_list", &worktrees)
+            .script("rev_parse", "2222");
+        super::restack_branch(&git, &config, "main").unwrap();
+        assert_eq!(
+            calls(&git, &["rebase", "rebase_onto"]),
+            vec!["rebase main /repo.git/foo", "rebase_onto feature/foo 2222 /repo.git/api"]
+        );
+        assert_eq!(Journal::load(&dir).unwrap(), None);
+    }
+
+    #[test]
+    fn update() {
+        let (git, dir) = mock("update", 1);
+        git.script("current_branch", "feature/foo")
+            .script("local_changes", "true")
+            .script("rev_parse", "1111")
+            .script("rev_parse", "aaaa")
+            .script("stash_list", "aaaa\nbbbb");
+        super::update_branch(&git, &Config::default(), "/repo.git/foo").unwrap();
+        assert_eq!(
+            calls(&git, &["rev_parse", "local_changes", "stash_push", "rebase", "stash_list", "stash_pop"]),
+            vec![
+                "rev_parse HEAD /repo.git/foo",
+                "local_changes /repo.git/foo",
+                "stash_push /repo.git/foo",
+                "rev_parse refs/stash /repo.git/foo",
+                "rebase main /repo.git/foo",
+                "stash_list /repo.git/foo",
+                "stash_pop /repo.git/foo",
+                "rev_parse HEAD /repo.git/foo",
+            ]
+        );
+        let operations = oplog::load(&dir).unwrap();
+        assert_eq!(operations[0].stashes[0].state, StashState::Popped);
+    }
+
+    #[test]
+    fn push() {
+        let (git, _) = mock("push", 1);
+        git.script("current_branch", "feature/foo").script("worktree_list", WORKTREES);
+        super::push_branch(&git, &Config::default(), "/repo.git/foo", true).unwrap();
+        assert_eq!(
+            calls(&git, &["rebase", "push"]),
+            vec![
+                "rebase feature/foo /repo.git/main".to_string(),
+                format!("push origin main {}", &*constants::CWD),
+            ]
+        );
+    }
+
+    #[test]
+    fn push_continue() {
+        let (git, dir) = mock("continue", 2);
+        git.script("current_branch", "feature/foo")
+            .script("worktree_list", WORKTREES)
+            .script("local_changes", "true")
+            .script("rev_parse", "1111")
+            .script("rev_parse", "aaaa")
+            .script_err("rebase", make_err!(Conflict, "conflict"))
+            .script("rebase_in_progress", "true");
+        assert!(super::push_branch(&git, &Config::default(), "/repo.git/foo", true).is_err());
+        assert!(calls(&git, &["stash_pop", "push"]).is_empty());
+        let journal = Journal::load(&dir).unwrap().unwrap();
+        assert_eq!(journal.steps[0].state, StepState::Rebasing);
+        assert_eq!(journal.steps[0].stash.as_deref(), Some("aaaa"));
+
+        // another operation can't start while one is in progress
+        let other = MockBackend::new();
+        other.script("git_dir", &dir).script("current_branch", "feature/foo");
+        assert!(super::update_branch(&other, &Config::default(), "/repo.git/foo").is_err());
+
+        git.script("rebase_in_progress", "true").script("stash_list", "aaaa");
+        super::resume_operation(&git).unwrap();
+        assert_eq!(
+            calls(&git, &["rebase_continue", "stash_pop", "push"]),
+            vec![
+                "rebase_continue /repo.git/main".to_string(),
+                "stash_pop /repo.git/main".to_string(),
+                format!("push origin main {}", &*constants::CWD),
+            ]
+        );
+        assert_eq!(Journal::load(&dir).unwrap(), None);
+    }
+
+    #[test]
+    fn abort_done() {
+        let (git, dir) = mock("done", 1);
+        let mut journal = Journal::new("restack");
+        journal.step("feature/foo", "main", "/repo.git/foo");
+        journal.step("feature/api", "feature/foo", "/repo.git/api");
+        journal.steps[0].state = StepState::Done;
+        journal.steps[0].head = Some("1111".to_string());
+        journal.steps[0].stash = Some("aaaa".to_string());
+        journal.steps[0].restored = Some(StashState::Popped);
+        journal.save(&dir).unwrap();
+        git.script("local_changes", "true");
+        super::abort_operation(&git).unwrap();
+        assert_eq!(
+            calls(&git, &["stash_push", "reset_keep", "stash_pop"]),
+            vec![
+                "stash_push /repo.git/foo",
+                "reset_keep 1111 /repo.git/foo",
+                "stash_pop /repo.git/foo",
+            ]
+        );
+        assert_eq!(Journal::load(&dir).unwrap(), None);
+    }
+
+    #[test]
+    fn update_failed() {
+        let (git, dir) = mock("failed", 1);
+        git.script("current_branch", "feature/foo")
+            .script("local_changes", "true")
+            .script("rev_parse", "1111")
+            .script("rev_parse", "aaaa")
+            .script_err("rebase", make_err!(NotFound, "no such branch"))
+            .script("stash_list", "aaaa");
+        let err = super::update_branch(&git, &Config::default(), "/repo.git/foo").unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+        assert_eq!(calls(&git, &["stash_pop"]), vec!["stash_pop /repo.git/foo"]);
+        let journal = Journal::load(&dir).unwrap().unwrap();
+        assert_eq!(journal.steps[0].state, StepState::Pending);
+        assert_eq!(journal.steps[0].stash, None);
+    }
+
+    #[test]
+    fn update_abort() {
+        let (git, dir) = mock("abort", 2);
+        git.script("current_branch", "feature/foo")
+            .script("local_changes", "true")
+            .script("rev_parse", "1111")
+            .script("rev_parse", "aaaa")
+            .script_err("rebase", make_err!(Conflict, "conflict"))
+            .script("rebase_in_progress", "true");
+        assert!(super::update_branch(&git, &Config::default(), "/repo.git/foo").is_err());
+
+        git.script("rebase_in_progress", "true").script("stash_list", "bbbb\naaaa");
+        super::abort_operation(&git).unwrap();
+        assert_eq!(
+            calls(&git, &["rebase_abort", "reset_keep", "stash_apply", "stash_pop"]),
+            vec![
+                "rebase_abort /repo.git/foo",
+                "reset_keep 1111 /repo.git/foo",
+                "stash_apply aaaa /repo.git/foo",
+            ]
+        );
+        assert_eq!(Journal::load(&dir).unwrap(), None);
+    }
 }
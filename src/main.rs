@@ -1,8 +1,11 @@
 use clap::builder::styling;
 use gtea::cli;
+use gtea_lib::changelog;
+use gtea_lib::commit::history::LogFilter;
 use gtea_lib::prelude::*;
 
-use clap::{arg, Command};
+use clap::parser::ValueSource;
+use clap::{arg, ArgAction, Command};
 
 fn get_commands() -> Command {
     let effects = (styling::Effects::BOLD | styling::Effects::UNDERLINE).clear();
@@ -43,6 +46,15 @@ fn get_commands() -> Command {
                         .arg_required_else_help(false)
                         .arg(arg!(-p --push "push to remote")),
                 )
+                .subcommand(Command::new("tree").about("show the branch hierarchy"))
+                .subcommand(Command::new("continue").about("continue after resolving a conflict"))
+                .subcommand(Command::new("abort").about("roll back the operation in progress"))
+                .subcommand(
+                    Command::new("restack")
+                        .about("rebase branches stacked on a branch")
+                        .arg_required_else_help(false)
+                        .arg(arg!([BRANCH] "branch to restack from, defaults to the current branch")),
+                )
                 .subcommand(
                     Command::new("create")
                         .about("create child branch")
@@ -53,9 +65,103 @@ fn get_commands() -> Command {
                     Command::new("remove")
                         .about("remove a worktree")
                         .arg_required_else_help(true)
-                        .arg(arg!(<NAME> "name of branch")),
+                        .args([
+                            arg!(<NAME> "name of branch"),
+                            arg!(-f --force "remove even if work would be lost"),
+                        ]),
+                ),
+        )
+        .subcommand(
+            Command::new("backup")
+                .about("manage branch backups")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(
+                    Command::new("list")
+                        .about("list backups")
+                        .arg(arg!([BRANCH] "only list backups of BRANCH")),
+                )
+                .subcommand(
+                    Command::new("restore")
+                        .about("restore a branch from a backup")
+                        .arg_required_else_help(true)
+                        .args([
+                            arg!(<BRANCH> "branch to restore"),
+                            arg!([WHEN] "time of the backup, defaults to the latest"),
+                        ]),
+                )
+                .subcommand(
+                    Command::new("prune")
+                        .about("delete old backups")
+                        .arg(arg!(--"older-than" <AGE> "age like 12h, 30d or 2w").default_value("30d")),
+                ),
+        )
+        .subcommand(
+            Command::new("hooks")
+                .about("manage the commit-msg hook")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(Command::new("install").about("install the commit-msg hook"))
+                .subcommand(Command::new("uninstall").about("remove the commit-msg hook"))
+                .subcommand(Command::new("status").about("show the commit-msg hook")),
+        )
+        .subcommand(
+            Command::new("lint-msg")
+                .about("check a commit message file")
+                .arg_required_else_help(true)
+                .arg(arg!(<FILE> "commit message file")),
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("check commits for conventional commit conformance")
+                .args([
+                    arg!([RANGE] "commit range, defaults to the commits since the parent branch"),
+                    arg!(--format <FORMAT> "output format")
+                        .value_parser(["text", "json"])
+                        .default_value("text"),
+                ]),
+        )
+        .subcommand(
+            Command::new("version")
+                .about("compute release versions")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(
+                    Command::new("next")
+                        .about("print the next version from the commits since the last release")
+                        .arg(arg!(--explain "list the commits that decided the version")),
+                ),
+        )
+        .subcommand(
+            Command::new("release")
+                .about("bump versions, write the changelog, then commit and tag the release")
+                .args([
+                    arg!(--channel <CHANNEL> "release channel")
+                        .value_parser(["stable", "nightly"])
+                        .default_value("stable"),
+                    arg!(--"dry-run" "show the file changes and git commands without running them"),
+                ])
+                .subcommand(
+                    Command::new("promote")
+                        .about("fast-forward main to nightly and cut the stable release")
+                        .arg(arg!(--"dry-run" "show the file changes and git commands without running them")),
                 ),
         )
+        .subcommand(
+            Command::new("backport")
+                .about("cherry-pick commits onto other branches in their worktrees")
+                .visible_alias("ch")
+                .arg_required_else_help(true)
+                .args([
+                    arg!(<COMMIT> ... "commits to backport, in order"),
+                    arg!(--to <BRANCH> "target branches")
+                        .required(true)
+                        .num_args(1..)
+                        .action(ArgAction::Append),
+                ]),
+        )
+        .subcommand(Command::new("oplog").about("show the operation log"))
+        .subcommand(Command::new("undo").about("undo the last workflow operation"))
         .subcommand(Command::new("init").about("initialize git repo"))
         .subcommand(
             Command::new("clone")
@@ -82,7 +188,11 @@ fn get_commands() -> Command {
                 .args([
                     arg!(<TYPE> "type of the commit"),
                     arg!(-s --scope <SCOPE> "scope of the commit"),
-                    arg!(<MESSAGE> "commit message"),
+                    arg!([MESSAGE] "commit message, opens $EDITOR when omitted"),
+                    arg!(--body <BODY> "commit body"),
+                    arg!(--breaking <DESC> "mark as breaking change"),
+                    arg!(--trailer <TRAILER> "add a key=value trailer").action(ArgAction::Append),
+                    arg!(--issue <ISSUE> "add a Refs: trailer").action(ArgAction::Append),
                 ]),
         )
         .subcommand(
@@ -93,6 +203,21 @@ fn get_commands() -> Command {
                     arg!(-n --commits <NUM> "number of commits"),
                     arg!(-b --between <COMMIT> "changelog between commits").num_args(2),
                     arg!(-s --since <HASH> "changes since HASH"),
+                    arg!(--format <FORMAT> "output format")
+                        .value_parser(["terminal", "markdown", "json", "plain"])
+                        .default_value("terminal"),
+                    arg!(--write "add the changes to CHANGELOG.md"),
+                    arg!(--version <VERSION> "release version of the changes"),
+                    arg!(--template <TEMPLATE> "built-in template name or template file")
+                        .conflicts_with("format"),
+                    arg!(--rebuild "regenerate CHANGELOG.md from release tags"),
+                    arg!(--author <AUTHOR> "only commits by AUTHOR"),
+                    arg!(--path <GLOB> "only commits touching GLOB").action(ArgAction::Append),
+                    arg!(--"since-date" <DATE> "only commits after DATE"),
+                    arg!(--"until-date" <DATE> "only commits before DATE"),
+                    arg!(--type <TYPES> "only commits of TYPES").value_delimiter(','),
+                    arg!(--"exclude-type" <TYPES> "skip commits of TYPES").value_delimiter(','),
+                    arg!(--merges "include merge commits"),
                 ]),
         )
         .subcommand(
@@ -103,9 +228,16 @@ fn get_commands() -> Command {
         )
 }
 
-fn main() -> Result<()> {
+fn main() {
     pretty_env_logger::init();
 
+    if let Err(err) = run() {
+        eprintln!("Error: {err}");
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<()> {
     let matches = get_commands().get_matches();
 
     match matches.subcommand() {
@@ -127,31 +259,141 @@ fn main() -> Result<()> {
                     cli::workflow::update()?;
                     Ok(())
                 }
-                ("push", _) => {
-                    let upstream = sub_matches
-                        .get_one::<u8>("")
-                        .map(|v| *v)
-                        .unwrap_or(0);
-                    cli::workflow::push(upstream == 1)?;
+                ("push", sub_matches) => {
+                    let upstream = sub_matches.get_flag("push");
+                    cli::workflow::push(upstream)?;
+                    Ok(())
+                }
+                ("tree", _) => {
+                    cli::workflow::tree()?;
+                    Ok(())
+                }
+                ("continue", _) => {
+                    cli::workflow::resume()?;
+                    Ok(())
+                }
+                ("abort", _) => {
+                    cli::workflow::abort()?;
+                    Ok(())
+                }
+                ("restack", sub_matches) => {
+                    let branch = sub_matches.get_one::<String>("BRANCH");
+                    cli::workflow::restack(branch.map(|x| x.as_str()))?;
                     Ok(())
                 }
                 ("create", sub_matches) => {
                     let name = sub_matches
                         .get_one::<String>("NAME")
                         .ok_or(make_err!(Missing, "no branch name specified."))?;
-                    cli::workflow::create(&name)?;
+                    cli::workflow::create(name)?;
                     Ok(())
                 }
                 ("remove", sub_matches) => {
                     let name = sub_matches
                         .get_one::<String>("NAME")
                         .ok_or(make_err!(Missing, "no branch name specified."))?;
-                    cli::workflow::remove(&name)?;
+                    let force = sub_matches.get_flag("force");
+                    cli::workflow::remove(name, force)?;
+                    Ok(())
+                }
+                (&_, _) => Err(Error::Unexpected),
+            }
+        }
+        Some(("backup", sub_matches)) => {
+            let subcommand = sub_matches.subcommand().ok_or(make_err!())?;
+            match subcommand {
+                ("list", sub_matches) => {
+                    let branch = sub_matches.get_one::<String>("BRANCH");
+                    cli::backup::list(branch.map(|x| x.as_str()))?;
+                    Ok(())
+                }
+                ("restore", sub_matches) => {
+                    let branch = sub_matches
+                        .get_one::<String>("BRANCH")
+                        .ok_or(make_err!(Missing, "no branch name specified."))?;
+                    let when = sub_matches.get_one::<String>("WHEN");
+                    cli::backup::restore(branch, when.map(|x| x.as_str()))?;
+                    Ok(())
+                }
+                ("prune", sub_matches) => {
+                    let older_than = sub_matches
+                        .get_one::<String>("older-than")
+                        .ok_or(make_err!(Missing, "no age specified."))?;
+                    cli::backup::prune(older_than)?;
+                    Ok(())
+                }
+                (&_, _) => Err(Error::Unexpected),
+            }
+        }
+        Some(("hooks", sub_matches)) => {
+            let subcommand = sub_matches.subcommand().ok_or(make_err!())?;
+            match subcommand {
+                ("install", _) => {
+                    cli::hooks::install()?;
+                    Ok(())
+                }
+                ("uninstall", _) => {
+                    cli::hooks::uninstall()?;
+                    Ok(())
+                }
+                ("status", _) => {
+                    cli::hooks::status()?;
+                    Ok(())
+                }
+                (&_, _) => Err(Error::Unexpected),
+            }
+        }
+        Some(("lint-msg", sub_matches)) => {
+            let file = sub_matches
+                .get_one::<String>("FILE")
+                .ok_or(make_err!(Missing, "no file specified."))?;
+            cli::lint::lint_msg(file)?;
+            Ok(())
+        }
+        Some(("lint", sub_matches)) => {
+            let range = sub_matches.get_one::<String>("RANGE");
+            let format = sub_matches
+                .get_one::<String>("format")
+                .ok_or(make_err!(Missing, "no format specified."))?;
+            cli::lint::lint(range.map(|x| x.as_str()), format)?;
+            Ok(())
+        }
+        Some(("version", sub_matches)) => {
+            let subcommand = sub_matches.subcommand().ok_or(make_err!())?;
+            match subcommand {
+                ("next", sub_matches) => {
+                    cli::version::next(sub_matches.get_flag("explain"))?;
                     Ok(())
                 }
                 (&_, _) => Err(Error::Unexpected),
             }
         }
+        Some(("release", sub_matches)) => match sub_matches.subcommand() {
+            Some(("promote", sub_matches)) => {
+                cli::release::promote(sub_matches.get_flag("dry-run"))?;
+                Ok(())
+            }
+            Some((&_, _)) => Err(Error::Unexpected),
+            None => {
+                let channel = sub_matches.get_one::<String>("channel").ok_or(make_err!())?;
+                cli::release::release(channel.parse()?, sub_matches.get_flag("dry-run"))?;
+                Ok(())
+            }
+        },
+        Some(("backport", sub_matches)) => {
+            let commits = sub_matches.get_many::<String>("COMMIT").unwrap_or_default().collect();
+            let targets = sub_matches.get_many::<String>("to").unwrap_or_default().collect();
+            cli::backport::backport(commits, targets)?;
+            Ok(())
+        }
+        Some(("oplog", _)) => {
+            cli::oplog::log()?;
+            Ok(())
+        }
+        Some(("undo", _)) => {
+            cli::oplog::undo()?;
+            Ok(())
+        }
         Some(("init", _)) => {
             cli::repo::init()?;
             Ok(())
@@ -181,28 +423,63 @@ fn main() -> Result<()> {
                 .get_one::<String>("TYPE")
                 .ok_or(make_err!(Missing, "no type specified."))?;
             let scope = sub_matches.get_one::<String>("scope");
-            let msg = sub_matches
-                .get_one::<String>("MESSAGE")
-                .ok_or(make_err!(Missing, "no message specified."))?;
-            cli::git::commit(&t, scope, &msg)?;
+            let msg = sub_matches.get_one::<String>("MESSAGE");
+            let options = cli::git::CommitOptions {
+                body: sub_matches.get_one::<String>("body"),
+                breaking: sub_matches.get_one::<String>("breaking"),
+                trailers: sub_matches.get_many::<String>("trailer").unwrap_or_default().collect(),
+                issues: sub_matches.get_many::<String>("issue").unwrap_or_default().collect(),
+            };
+            cli::git::commit(t, scope, msg, options)?;
             Ok(())
         }
         Some(("changelog", sub_matches)) => {
             let n = sub_matches.get_one::<String>("commits");
             let commits = sub_matches.get_many::<String>("between");
             let since = sub_matches.get_one::<String>("since");
-            if let Some(n_bind) = n {
-                cli::git::changelog_for_n(&n_bind)?;
+            // an explicit format wins over a configured template
+            let format: Option<changelog::Format> = match sub_matches.value_source("format") {
+                Some(ValueSource::CommandLine) => sub_matches
+                    .get_one::<String>("format")
+                    .map(|x| x.parse())
+                    .transpose()?,
+                _ => None,
+            };
+            let range = if let Some(n_bind) = n {
+                Some(cli::changelog::range_for_n(n_bind))
             } else if let Some(commits_bind) = commits {
                 if commits_bind.len() != 2 {
                     return Err(make_err!(Missing, "unexpected amount of arguments."));
                 }
                 let c: Vec<String> = commits_bind.map(|hash| hash.to_string()).collect();
-                cli::git::changelog_between(&c[0], &c[1])?;
-            } else if let Some(since_bind) = since {
-                cli::git::changelog_since(since_bind)?;
+                Some(cli::changelog::range_between(&c[0], &c[1]))
+            } else {
+                since.map(|since_bind| cli::changelog::range_since(since_bind))
+            };
+            let strings = |id: &str| -> Vec<String> {
+                sub_matches.get_many::<String>(id).unwrap_or_default().cloned().collect()
+            };
+            let filter = changelog::Filter {
+                log: LogFilter {
+                    author: sub_matches.get_one::<String>("author").cloned(),
+                    paths: strings("path"),
+                    since: sub_matches.get_one::<String>("since-date").cloned(),
+                    until: sub_matches.get_one::<String>("until-date").cloned(),
+                    merges: sub_matches.get_flag("merges"),
+                    ..Default::default()
+                },
+                types: strings("type"),
+                exclude_types: strings("exclude-type"),
+            };
+            let version = sub_matches.get_one::<String>("version");
+            if sub_matches.get_flag("rebuild") {
+                cli::changelog::rebuild(&filter)?;
+            } else if sub_matches.get_flag("write") {
+                cli::changelog::write(range.as_deref(), version.map(|x| x.as_str()), &filter)?;
             } else {
-                return Err(make_err!(Missing, "argument missing."));
+                let range = range.ok_or(make_err!(Missing, "argument missing."))?;
+                let template = sub_matches.get_one::<String>("template");
+                cli::changelog::show(&range, format, template.map(|x| x.as_str()), version.map(|x| x.as_str()), &filter)?;
             }
             Ok(())
         }